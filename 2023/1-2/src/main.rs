@@ -1,90 +1,254 @@
-use std::fs;
-
-const DIGITS: [(&str, u8); 18] = [
-    ("one", 1),
-    ("two", 2),
-    ("three", 3),
-    ("four", 4),
-    ("five", 5),
-    ("six", 6),
-    ("seven", 7),
-    ("eight", 8),
-    ("nine", 9),
-    ("1", 1),
-    ("2", 2),
-    ("3", 3),
-    ("4", 4),
-    ("5", 5),
-    ("6", 6),
-    ("7", 7),
-    ("8", 8),
-    ("9", 9),
-];
+use std::{env, fs, process, str::FromStr};
+
+mod matcher;
+use matcher::*;
+
+// What a word counts as depending on which end of the line it's at, so "ten" can be a 1 in front
+// and a 0 in the back and the calibration value still comes out as first * 10 + last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Digits {
+    leading: u32,
+    trailing: u32,
+}
 
-fn digit_forward(s: &str) -> Option<u8> {
-    for (pattern, value) in DIGITS {
-        if s.starts_with(pattern) {
-            return Some(value);
+impl Digits {
+    const fn single(digit: u32) -> Self {
+        Digits {
+            leading: digit,
+            trailing: digit,
         }
     }
-    None
 }
 
-fn search_digit_forward(s: &str) -> u8 {
-    let len = s.len();
-    for i in 0..len {
-        let digit = digit_forward(&s[i..len]);
-        if let Some(i) = digit {
-            return i;
+type Vocabulary = &'static [(&'static str, Digits)];
+
+const NUMERALS: Vocabulary = &[
+    ("1", Digits::single(1)),
+    ("2", Digits::single(2)),
+    ("3", Digits::single(3)),
+    ("4", Digits::single(4)),
+    ("5", Digits::single(5)),
+    ("6", Digits::single(6)),
+    ("7", Digits::single(7)),
+    ("8", Digits::single(8)),
+    ("9", Digits::single(9)),
+];
+
+const ENGLISH: Vocabulary = &[
+    ("one", Digits::single(1)),
+    ("two", Digits::single(2)),
+    ("three", Digits::single(3)),
+    ("four", Digits::single(4)),
+    ("five", Digits::single(5)),
+    ("six", Digits::single(6)),
+    ("seven", Digits::single(7)),
+    ("eight", Digits::single(8)),
+    ("nine", Digits::single(9)),
+];
+
+// Not in the puzzle, but nothing stops the elves from writing these.
+const ENGLISH_EXTRA: Vocabulary = &[
+    ("zero", Digits::single(0)),
+    ("0", Digits::single(0)),
+    (
+        "ten",
+        Digits {
+            leading: 1,
+            trailing: 0,
+        },
+    ),
+];
+
+const GERMAN: Vocabulary = &[
+    ("eins", Digits::single(1)),
+    ("zwei", Digits::single(2)),
+    ("drei", Digits::single(3)),
+    ("vier", Digits::single(4)),
+    ("fünf", Digits::single(5)),
+    ("sechs", Digits::single(6)),
+    ("sieben", Digits::single(7)),
+    ("acht", Digits::single(8)),
+    ("neun", Digits::single(9)),
+];
+
+const VOCABULARIES: [(&str, Vocabulary); 4] = [
+    ("numerals", NUMERALS),
+    ("english", ENGLISH),
+    ("english-extra", ENGLISH_EXTRA),
+    ("german", GERMAN),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    // First gold star
+    Digits,
+    // Second gold star
+    DigitsAndWords,
+}
+
+impl Mode {
+    fn vocabulary(&self) -> Vec<(&'static str, Digits)> {
+        match self {
+            Mode::Digits => NUMERALS.to_vec(),
+            Mode::DigitsAndWords => [NUMERALS, ENGLISH].concat(),
         }
     }
-    panic!()
 }
 
-fn digit_backward(s: &str) -> Option<u8> {
-    for (pattern, value) in DIGITS {
-        if s.ends_with(pattern) {
-            return Some(value);
+#[derive(Debug)]
+struct NoDigitError {
+    line_number: usize,
+}
+
+#[derive(Debug)]
+struct LineReport {
+    line_number: usize,
+    // Byte positions are in `first.start..first.end` and `last.start..last.end`.
+    first: Match<Digits>,
+    last: Match<Digits>,
+    value: u32,
+}
+
+#[derive(Debug)]
+struct Report {
+    lines: Vec<LineReport>,
+    // Line numbers without anything to match.
+    skipped: Vec<usize>,
+}
+
+impl Report {
+    fn sum(&self) -> u32 {
+        self.lines.iter().map(|x| x.value).sum()
+    }
+
+    // Like `sum`, but a line without a digit is an error instead of being left out.
+    fn strict_sum(&self) -> Result<u32, NoDigitError> {
+        match self.skipped.first() {
+            Some(&line_number) => Err(NoDigitError { line_number }),
+            None => Ok(self.sum()),
         }
     }
-    None
 }
 
-fn search_digit_backward(s: &str) -> u8 {
-    let len = s.len();
-    for i in (1..=len).rev() {
-        let digit = digit_backward(&s[0..i]);
-        if let Some(i) = digit {
-            return i;
+#[derive(Debug)]
+struct Input {
+    lines: Vec<String>,
+}
+
+impl FromStr for Input {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Input {
+            lines: s.lines().map(|x| x.to_string()).collect(),
+        })
+    }
+}
+
+fn first_and_last(matcher: &Matcher<Digits>, s: &str) -> Option<(Match<Digits>, Match<Digits>)> {
+    let mut matches = matcher.find_iter(s);
+    let first = matches.next()?;
+    // Matches come out by where they end, so the first one to start isn't necessarily the first one out.
+    Some(matches.fold((first, first), |(first, last), x| {
+        (
+            if x.start < first.start { x } else { first },
+            if x.end > last.end { x } else { last },
+        )
+    }))
+}
+
+impl Input {
+    fn calibrate(&self, vocabulary: &[(&str, Digits)]) -> Report {
+        let matcher = Matcher::new(vocabulary);
+        let mut report = Report {
+            lines: vec![],
+            skipped: vec![],
+        };
+        for (i, line) in self.lines.iter().enumerate() {
+            match first_and_last(&matcher, line) {
+                Some((first, last)) => report.lines.push(LineReport {
+                    line_number: i + 1,
+                    first,
+                    last,
+                    value: first.value.leading * 10 + last.value.trailing,
+                }),
+                None => report.skipped.push(i + 1),
+            }
         }
+        report
+    }
+
+    fn calibrate_mode(&self, mode: Mode) -> Report {
+        self.calibrate(&mode.vocabulary())
     }
-    panic!()
 }
 
-fn integer_from_line_slice(s: &str) -> u8 {
-    let first_digit: u8 = search_digit_forward(s);
-    let last_digit: u8 = search_digit_backward(s);
+fn print_lines(input: &Input, report: &Report) {
+    for line in &report.lines {
+        let text = &input.lines[line.line_number - 1];
+        println!(
+            "{:>5} {:>3} {:?}@{} {:?}@{}",
+            line.line_number,
+            line.value,
+            &text[line.first.start..line.first.end],
+            line.first.start,
+            &text[line.last.start..line.last.end],
+            line.last.start,
+        );
+    }
+    for line_number in &report.skipped {
+        println!("{:>5} skipped", line_number);
+    }
+}
 
-    first_digit * 10 + last_digit
+fn print_sum(name: &str, report: &Report) {
+    match report.strict_sum() {
+        Ok(sum) => println!("{}: {}", name, sum),
+        Err(NoDigitError { line_number }) => {
+            eprintln!("{}: line {} has no digit in it", name, line_number);
+            eprintln!(
+                "{}: {} without the {} skipped lines",
+                name,
+                report.sum(),
+                report.skipped.len()
+            );
+        }
+    }
 }
 
 fn main() {
-    let input_string = fs::read_to_string("input").unwrap();
+    let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
 
-    let mut slice = String::new();
-    let mut sum: u32 = 0;
+    // `cargo run -- numerals german` and so on, both parts of the puzzle otherwise.
+    // `-v` lists what got matched on every line.
+    let (flags, names): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|x| x.starts_with('-'));
+    let verbose = flags.iter().any(|x| x == "-v");
 
-    for c in input_string.chars() {
-        match c {
-            '\n' => {
-                sum += integer_from_line_slice(&slice) as u32;
-                slice = String::new();
-            }
-            _ => {
-                slice.push(c);
-            }
+    if names.is_empty() {
+        let digits = input.calibrate_mode(Mode::Digits);
+        let words = input.calibrate_mode(Mode::DigitsAndWords);
+        if verbose {
+            print_lines(&input, &words);
         }
+        print_sum("part 1", &digits);
+        print_sum("part 2", &words);
+        return;
     }
 
-    println!("{}", sum);
+    let mut vocabulary = vec![];
+    for name in &names {
+        match VOCABULARIES.iter().find(|(x, _)| x == name) {
+            Some((_, words)) => vocabulary.extend_from_slice(words),
+            None => {
+                eprintln!("unknown vocabulary {:?}", name);
+                process::exit(1);
+            }
+        }
+    }
+    let report = input.calibrate(&vocabulary);
+    if verbose {
+        print_lines(&input, &report);
+    }
+    print_sum(&names.join(" + "), &report);
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+// Aho-Corasick. Every pattern gets walked through at the same time, one byte at a time.
+#[derive(Debug)]
+struct Node<T> {
+    next: HashMap<u8, usize>,
+    // Where to continue from when `next` has nothing: the longest proper suffix that's also in the trie.
+    fail: usize,
+    // Patterns ending here, as (length in bytes, value). Includes the ones reachable through `fail`.
+    outputs: Vec<(usize, T)>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Self {
+        Node {
+            next: HashMap::new(),
+            fail: 0,
+            outputs: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Matcher<T> {
+    nodes: Vec<Node<T>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<T> {
+    pub start: usize,
+    pub end: usize,
+    pub value: T,
+}
+
+impl<T: Copy> Matcher<T> {
+    pub fn new<'a, I>(vocabulary: I) -> Self
+    where
+        I: IntoIterator<Item = &'a (&'a str, T)>,
+        T: 'a,
+    {
+        let mut nodes = vec![Node::new()];
+        for (pattern, value) in vocabulary {
+            let mut node = 0;
+            for &byte in pattern.as_bytes() {
+                node = match nodes[node].next.get(&byte) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::new());
+                        let next = nodes.len() - 1;
+                        nodes[node].next.insert(byte, next);
+                        next
+                    }
+                }
+            }
+            nodes[node].outputs.push((pattern.len(), *value));
+        }
+
+        // Breadth first, so the fail target is always done before we get to a node.
+        let mut queue: std::collections::VecDeque<usize> =
+            nodes[0].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let edges: Vec<(u8, usize)> = nodes[node].next.iter().map(|(&b, &n)| (b, n)).collect();
+            for (byte, child) in edges {
+                let mut fail = nodes[node].fail;
+                let child_fail = loop {
+                    if let Some(&next) = nodes[fail].next.get(&byte) {
+                        break next;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = nodes[fail].fail;
+                };
+                nodes[child].fail = child_fail;
+                let inherited = nodes[child_fail].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        Matcher { nodes }
+    }
+
+    fn step(&self, mut node: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].next.get(&byte) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    // Every match, overlapping ones included, in the order their ends show up.
+    pub fn find_iter<'m, 'h>(&'m self, haystack: &'h str) -> Matches<'m, 'h, T> {
+        Matches {
+            matcher: self,
+            haystack: haystack.as_bytes(),
+            position: 0,
+            node: 0,
+            pending: [].iter(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Matches<'m, 'h, T> {
+    matcher: &'m Matcher<T>,
+    haystack: &'h [u8],
+    position: usize,
+    node: usize,
+    pending: std::slice::Iter<'m, (usize, T)>,
+}
+
+impl<T: Copy> Iterator for Matches<'_, '_, T> {
+    type Item = Match<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&(length, value)) = self.pending.next() {
+                return Some(Match {
+                    start: self.position - length,
+                    end: self.position,
+                    value,
+                });
+            }
+            let &byte = self.haystack.get(self.position)?;
+            self.node = self.matcher.step(self.node, byte);
+            self.position += 1;
+            self.pending = self.matcher.nodes[self.node].outputs.iter();
+        }
+    }
+}
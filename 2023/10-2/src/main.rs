@@ -1,6 +1,6 @@
 #![warn(missing_debug_implementations)]
 
-use std::{fs, ops::Neg, str::FromStr};
+use std::{fmt, fs, ops::Neg, process, str::FromStr};
 
 #[derive(Debug)]
 struct ParseError {}
@@ -16,6 +16,13 @@ impl Neg for Vector {
     }
 }
 
+impl Vector {
+    // With y going down, so east turns into north.
+    fn turn_left(self) -> Vector {
+        Vector(self.1, -self.0)
+    }
+}
+
 const WEST: Vector = Vector(-1, 0);
 const EAST: Vector = Vector(1, 0);
 const NORTH: Vector = Vector(0, -1);
@@ -77,6 +84,45 @@ impl PipeCell {
         }
     }
 
+    fn to_char(self) -> char {
+        match self {
+            PipeCell::None => '.',
+            PipeCell::Start => 'S',
+            PipeCell::Horizontal => '-',
+            PipeCell::Vertical => '|',
+            PipeCell::SouthEast => 'F',
+            PipeCell::SouthWest => '7',
+            PipeCell::NorthEast => 'L',
+            PipeCell::NorthWest => 'J',
+        }
+    }
+
+    fn box_char(self) -> char {
+        match self {
+            PipeCell::None => ' ',
+            PipeCell::Start => 'S',
+            PipeCell::Horizontal => '─',
+            PipeCell::Vertical => '│',
+            PipeCell::SouthEast => '┌',
+            PipeCell::SouthWest => '┐',
+            PipeCell::NorthEast => '└',
+            PipeCell::NorthWest => '┘',
+        }
+    }
+
+    fn from_connections(a: Vector, b: Vector) -> Option<PipeCell> {
+        [
+            PipeCell::Horizontal,
+            PipeCell::Vertical,
+            PipeCell::SouthEast,
+            PipeCell::SouthWest,
+            PipeCell::NorthEast,
+            PipeCell::NorthWest,
+        ]
+        .into_iter()
+        .find(|x| x.connections() == Some((a, b)) || x.connections() == Some((b, a)))
+    }
+
     fn other_connection(&self, position: Vector) -> Option<Vector> {
         let connections = self.connections()?;
         if connections.0 == position {
@@ -89,6 +135,7 @@ impl PipeCell {
     }
 }
 
+// Which side of the loop something ended up on, going around it in walking order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FloodFillIndex {
     Border,
@@ -107,6 +154,123 @@ impl FloodFillIndex {
     }
 }
 
+// Every tile of the loop in walking order, starting at S. S itself has been swapped for the pipe it has to be.
+#[derive(Debug)]
+struct Loop {
+    start_tile: PipeCell,
+    vertices: Vec<Position>,
+}
+
+impl Loop {
+    // Shoelace, counting the tiles' centers as the corners of the polygon. Doubled so it stays whole.
+    fn doubled_area(&self) -> usize {
+        let sum: i64 = self
+            .vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+            .sum();
+        sum.unsigned_abs() as usize
+    }
+
+    // Pick's theorem, A = I + B / 2 - 1, with every loop tile a boundary point.
+    // Rearranged to 2I = 2A - B + 2, which is never negative.
+    fn enclosed_tiles(&self) -> usize {
+        (self.doubled_area() + 2 - self.vertices.len()) / 2
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipeRole {
+    MainLoop,
+    OtherLoop,
+    // Doesn't close up, at least one end points at nothing.
+    Fragment,
+}
+
+#[derive(Debug)]
+enum WalkEnd {
+    // The pipe at this position leads off the map.
+    OffGrid(Position),
+    // We walked into this, but it doesn't have an opening towards where we came from.
+    NotConnected(Position, PipeCell),
+    // Came back into S the same way we left it.
+    Reversed,
+}
+
+#[derive(Debug)]
+enum LoopError {
+    NoStart,
+    // How the walk went in every direction out of S.
+    NoLoop(Vec<(Vector, WalkEnd)>),
+}
+
+impl Vector {
+    fn name(&self) -> &'static str {
+        match *self {
+            WEST => "west",
+            EAST => "east",
+            NORTH => "north",
+            SOUTH => "south",
+            _ => "somewhere odd",
+        }
+    }
+}
+
+impl fmt::Display for LoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopError::NoStart => write!(f, "there's no S in the maze"),
+            LoopError::NoLoop(dead_ends) => {
+                write!(f, "no loop closes through S")?;
+                for (direction, end) in dead_ends {
+                    write!(f, "\n  going {}: ", direction.name())?;
+                    match end {
+                        WalkEnd::OffGrid(p) => {
+                            write!(f, "the pipe at {},{} leads off the map", p.0, p.1)?
+                        }
+                        WalkEnd::NotConnected(p, PipeCell::None) => {
+                            write!(f, "ran into the ground at {},{}", p.0, p.1)?
+                        }
+                        WalkEnd::NotConnected(p, pipe) => write!(
+                            f,
+                            "ran into {} at {},{} from a side it isn't open on",
+                            pipe.to_char(),
+                            p.0,
+                            p.1
+                        )?,
+                        WalkEnd::Reversed => write!(f, "came back in the way it left")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Ansi,
+}
+
+// Flood fill result, one entry per tile. `outside` is whichever side made it to the edge.
+#[derive(Debug)]
+struct Enclosure {
+    tiles: Vec<Vec<FloodFillIndex>>,
+    outside: FloodFillIndex,
+}
+
+impl Enclosure {
+    fn inside_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .flatten()
+            .filter(|x| **x == self.outside.invert())
+            .count()
+    }
+}
+
 #[derive(Debug)]
 struct Input {
     grid: Vec<Vec<PipeCell>>,
@@ -128,6 +292,10 @@ impl FromStr for Input {
         }
         let width = grid[0].len();
         let height = grid.len();
+        // Everything else sizes itself off the first row.
+        if grid.iter().any(|x| x.len() != width) {
+            return Err(ParseError {});
+        }
         Ok(Input {
             grid,
             width,
@@ -152,79 +320,297 @@ impl Input {
         self.grid[position.1][position.0]
     }
 
-    fn border(&self) -> Option<Vec<Vec<bool>>> {
-        let start_position = self.start_position()?;
+    fn get(&self, position: Position) -> Option<PipeCell> {
+        self.grid.get(position.1)?.get(position.0).copied()
+    }
+
+    // S stands in for whatever pipe the loop needs there.
+    fn pipe_at(&self, position: Position, start_tile: PipeCell) -> Option<PipeCell> {
+        match self.get(position)? {
+            PipeCell::Start => Some(start_tile),
+            x => Some(x),
+        }
+    }
+
+    // Neighbors that this pipe points at and that point right back.
+    fn linked(&self, position: Position, start_tile: PipeCell) -> Vec<Position> {
+        let Some((a, b)) = self
+            .pipe_at(position, start_tile)
+            .and_then(|x| x.connections())
+        else {
+            return vec![];
+        };
+        [a, b]
+            .into_iter()
+            .filter_map(|direction| {
+                let next = position.checked_add(direction)?;
+                self.pipe_at(next, start_tile)?
+                    .other_connection(-direction)
+                    .map(|_| next)
+            })
+            .collect()
+    }
 
-        for starting_direction in ADJACENT {
-            let mut position = start_position;
-            let mut direction = starting_direction;
+    // Follows the pipes from S until it's back at S, or says where it got stuck.
+    fn walk(&self, start_position: Position, direction: Vector) -> Result<Loop, WalkEnd> {
+        let first_direction = direction;
+        let mut direction = direction;
+        let mut vertices = vec![];
+        let mut position = start_position;
+        loop {
+            vertices.push(position);
+            let next = position
+                .checked_add(direction)
+                .filter(|x| self.get(*x).is_some())
+                .ok_or(WalkEnd::OffGrid(position))?;
+            position = next;
+            let pipe_cell = self.at(position);
+            if pipe_cell == PipeCell::Start {
+                break;
+            }
+            direction = pipe_cell
+                .other_connection(-direction)
+                .ok_or(WalkEnd::NotConnected(position, pipe_cell))?;
+        }
 
-            let mut result_found = false;
+        Ok(Loop {
+            start_tile: PipeCell::from_connections(first_direction, -direction)
+                .ok_or(WalkEnd::Reversed)?,
+            vertices,
+        })
+    }
 
-            let mut border_grid = vec![];
-            border_grid.resize_with(self.height, || {
-                let mut v = vec![];
-                v.resize_with(self.width, || false);
-                v
-            });
+    // Every closed loop through S, once each no matter which way round it was found.
+    fn loops_through_start(&self) -> Result<Vec<Loop>, LoopError> {
+        let start_position = self.start_position().ok_or(LoopError::NoStart)?;
+
+        let mut loops: Vec<Loop> = vec![];
+        let mut dead_ends = vec![];
+        for direction in ADJACENT {
+            match self.walk(start_position, direction) {
+                Ok(found) => {
+                    if !loops.iter().any(|x| x.start_tile == found.start_tile) {
+                        loops.push(found);
+                    }
+                }
+                Err(end) => dead_ends.push((direction, end)),
+            }
+        }
 
-            loop {
-                border_grid[position.1][position.0] = true;
-                position = match position.checked_add(direction) {
-                    Some(x) => x,
-                    None => break,
-                };
-                let pipe_cell = self.at(position);
+        if loops.is_empty() {
+            return Err(LoopError::NoLoop(dead_ends));
+        }
+        Ok(loops)
+    }
+
+    // When a few loops go through S, the longest one is the one the animal is in.
+    fn main_loop(&self) -> Result<Loop, LoopError> {
+        Ok(self
+            .loops_through_start()?
+            .into_iter()
+            .max_by_key(|x| x.vertices.len())
+            .unwrap())
+    }
+
+    // Dead ends get peeled off until nothing changes, whatever's left has two links and so is a loop.
+    fn classify(&self, main_loop: &Loop) -> Vec<Vec<Option<PipeRole>>> {
+        let start_tile = main_loop.start_tile;
+        let mut roles = vec![vec![None; self.width]; self.height];
+        let mut links = vec![vec![0; self.width]; self.height];
+        let mut peel = vec![];
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, pipe_cell) in row.iter().enumerate() {
+                if *pipe_cell == PipeCell::None {
+                    continue;
+                }
+                roles[y][x] = Some(PipeRole::OtherLoop);
+                links[y][x] = self.linked(Position(x, y), start_tile).len();
+                if links[y][x] < 2 {
+                    roles[y][x] = Some(PipeRole::Fragment);
+                    peel.push(Position(x, y));
+                }
+            }
+        }
 
-                if pipe_cell == PipeCell::Start {
-                    result_found = true;
-                    break;
+        while let Some(position) = peel.pop() {
+            for next in self.linked(position, start_tile) {
+                if roles[next.1][next.0] == Some(PipeRole::OtherLoop) {
+                    links[next.1][next.0] -= 1;
+                    if links[next.1][next.0] < 2 {
+                        roles[next.1][next.0] = Some(PipeRole::Fragment);
+                        peel.push(next);
+                    }
                 }
+            }
+        }
 
-                direction = match pipe_cell.other_connection(-direction) {
-                    Some(x) => x,
-                    None => break,
-                };
+        for position in &main_loop.vertices {
+            roles[position.1][position.0] = Some(PipeRole::MainLoop);
+        }
+        roles
+    }
+
+    // Draws the loop at `scale` times the size, so there's always room to squeeze between two
+    // pipes that touch. Then both sides of the loop get flooded and looked at tile by tile again.
+    fn flood_fill(&self, main_loop: &Loop, scale: usize) -> Enclosure {
+        assert!(
+            scale >= 2,
+            "pipes only come apart at twice the size or more"
+        );
+
+        // A free ring all around, so the outside is always connected.
+        let width = self.width * scale + 2;
+        let height = self.height * scale + 2;
+        let center = |p: Position| (1 + p.0 * scale + scale / 2, 1 + p.1 * scale + scale / 2);
+        let offset = |(x, y): (usize, usize), v: Vector, k: usize| {
+            (
+                (x as i64 + v.0 as i64 * k as i64) as usize,
+                (y as i64 + v.1 as i64 * k as i64) as usize,
+            )
+        };
+
+        let mut fine = vec![vec![FloodFillIndex::None; width]; height];
+        let steps: Vec<(Position, Vector)> = main_loop
+            .vertices
+            .iter()
+            .zip(main_loop.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, Vector(b.0 as i32 - a.0 as i32, b.1 as i32 - a.1 as i32)))
+            .collect();
+        for (position, direction) in &steps {
+            for k in 0..scale {
+                let (x, y) = offset(center(*position), *direction, k);
+                fine[y][x] = FloodFillIndex::Border;
             }
+        }
 
-            if result_found {
-                return Some(border_grid);
+        // Everything right next to the loop can be told apart by which hand it's on.
+        let mut queue = std::collections::VecDeque::new();
+        for (position, direction) in &steps {
+            for k in 0..scale {
+                let cell = offset(center(*position), *direction, k);
+                for (side, label) in [
+                    (direction.turn_left(), FloodFillIndex::Left),
+                    (-direction.turn_left(), FloodFillIndex::Right),
+                ] {
+                    let (x, y) = offset(cell, side, 1);
+                    if fine[y][x] == FloodFillIndex::None {
+                        fine[y][x] = label;
+                        queue.push_back((x, y));
+                    }
+                }
             }
         }
 
-        None
+        while let Some((x, y)) = queue.pop_front() {
+            for direction in ADJACENT {
+                let Some(next) = Position(x, y).checked_add(direction) else {
+                    continue;
+                };
+                if next.0 < width && next.1 < height && fine[next.1][next.0] == FloodFillIndex::None
+                {
+                    fine[next.1][next.0] = fine[y][x];
+                    queue.push_back((next.0, next.1));
+                }
+            }
+        }
+
+        Enclosure {
+            tiles: (0..self.height)
+                .map(|y| {
+                    (0..self.width)
+                        .map(|x| {
+                            let (x, y) = center(Position(x, y));
+                            fine[y][x]
+                        })
+                        .collect()
+                })
+                .collect(),
+            outside: fine[0][0],
+        }
+    }
+
+    fn print_outside(&self, enclosure: &Enclosure) {
+        for (y, row) in enclosure.tiles.iter().enumerate() {
+            let line: String = row
+                .iter()
+                .enumerate()
+                .map(|(x, side)| match side {
+                    FloodFillIndex::Border => self.at(Position(x, y)).to_char(),
+                    side if *side == enclosure.outside => 'O',
+                    _ => 'I',
+                })
+                .collect();
+            println!("{}", line);
+        }
+    }
+
+    // The loop in box drawing characters, `█` inside and `·` outside, whatever pipes are lying
+    // around there included. `◆` marks the point farthest along the loop from S, if asked for.
+    fn render(
+        &self,
+        main_loop: &Loop,
+        enclosure: &Enclosure,
+        style: Style,
+        farthest: bool,
+    ) -> String {
+        const RESET: &str = "\x1b[0m";
+        let farthest = farthest.then(|| main_loop.vertices[main_loop.vertices.len() / 2]);
+
+        let mut out = String::new();
+        for (y, row) in enclosure.tiles.iter().enumerate() {
+            for (x, side) in row.iter().enumerate() {
+                let (c, colour) = match side {
+                    _ if farthest == Some(Position(x, y)) => ('◆', "\x1b[1;31m"),
+                    FloodFillIndex::Border => match self.at(Position(x, y)) {
+                        PipeCell::Start => (main_loop.start_tile.box_char(), "\x1b[1;33m"),
+                        pipe_cell => (pipe_cell.box_char(), "\x1b[36m"),
+                    },
+                    side if *side == enclosure.outside => ('·', "\x1b[90m"),
+                    _ => ('█', "\x1b[32m"),
+                };
+                match style {
+                    Style::Plain => out.push(c),
+                    Style::Ansi => {
+                        out.push_str(colour);
+                        out.push(c);
+                        out.push_str(RESET);
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out
     }
 
     fn solve(&self) -> Option<u32> {
-        let borders = self.border()?;
+        let main_loop = self.main_loop().ok()?;
+        let mut borders = vec![vec![false; self.width]; self.height];
+        for position in &main_loop.vertices {
+            borders[position.1][position.0] = true;
+        }
 
         let mut inside_count = 0;
-        for y in 0..self.height {
+        for (y, row) in borders.iter().enumerate() {
             let mut inside = false;
             let mut slash_diagonal = false;
-            for x in 0..self.width {
-                if borders[y][x] {
-                    match self.at(Position(x, y)) {
-                        PipeCell::Horizontal => (),
-                        PipeCell::Vertical => inside = !inside,
-                        PipeCell::SouthEast => slash_diagonal = true,
-                        PipeCell::NorthEast => slash_diagonal = false,
-                        PipeCell::SouthWest => {
-                            if !slash_diagonal {
-                                inside = !inside
-                            }
-                        }
-                        PipeCell::NorthWest | PipeCell::Start => {
-                            if slash_diagonal {
-                                inside = !inside
-                            }
-                        } // We fall into hardcoding hell
-                        _ => (),
-                    }
-                } else {
+            for (x, on_loop) in row.iter().enumerate() {
+                if !on_loop {
                     if inside {
                         inside_count += 1;
                     }
+                    continue;
+                }
+                let pipe_cell = match self.at(Position(x, y)) {
+                    PipeCell::Start => main_loop.start_tile,
+                    x => x,
+                };
+                match pipe_cell {
+                    PipeCell::Vertical => inside = !inside,
+                    PipeCell::SouthEast => slash_diagonal = true,
+                    PipeCell::NorthEast => slash_diagonal = false,
+                    PipeCell::SouthWest if !slash_diagonal => inside = !inside,
+                    PipeCell::NorthWest if slash_diagonal => inside = !inside,
+                    _ => (),
                 }
             }
         }
@@ -236,4 +622,68 @@ impl Input {
 fn main() {
     let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
     dbg!(&input.solve());
+
+    let main_loop = match input.main_loop() {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let roles = input.classify(&main_loop);
+    for role in [PipeRole::MainLoop, PipeRole::OtherLoop, PipeRole::Fragment] {
+        let count = roles.iter().flatten().filter(|x| **x == Some(role)).count();
+        println!("{:?}: {} pipes", role, count);
+    }
+    println!(
+        "{} loops through S",
+        input.loops_through_start().unwrap().len()
+    );
+    dbg!(
+        main_loop.start_tile,
+        main_loop.vertices.len(),
+        main_loop.doubled_area() as f64 / 2.0,
+        main_loop.enclosed_tiles()
+    );
+
+    // `--scale N` picks how far to blow the maze up for the flood fill, `--outside` draws what it found.
+    let args: Vec<String> = std::env::args().collect();
+    let scale = match args.iter().position(|x| x == "--scale") {
+        None => 3,
+        Some(x) => match args.get(x + 1).map(|x| x.parse::<usize>()) {
+            Some(Ok(scale)) if scale >= 2 => scale,
+            _ => {
+                eprintln!(
+                    "--scale needs a whole number of at least 2, got {}",
+                    args.get(x + 1)
+                        .map_or("nothing".to_string(), |x| format!("{:?}", x))
+                );
+                process::exit(1);
+            }
+        },
+    };
+    let enclosure = input.flood_fill(&main_loop, scale);
+    if Some(enclosure.inside_count() as u32) != input.solve() {
+        println!(
+            "warning: the flood fill found {} inside, the scanline didn't",
+            enclosure.inside_count()
+        );
+    }
+    if args.iter().any(|x| x == "--outside") {
+        input.print_outside(&enclosure);
+    }
+
+    // `--draw` for plain text, `--draw-ansi` for colours, `--farthest` adds the first star's answer.
+    let style = match (
+        args.iter().any(|x| x == "--draw"),
+        args.iter().any(|x| x == "--draw-ansi"),
+    ) {
+        (_, true) => Some(Style::Ansi),
+        (true, false) => Some(Style::Plain),
+        (false, false) => None,
+    };
+    if let Some(style) = style {
+        let farthest = args.iter().any(|x| x == "--farthest");
+        print!("{}", input.render(&main_loop, &enclosure, style, farthest));
+    }
 }
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use std::{iter::FusedIterator, ops::Add};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -132,7 +134,7 @@ impl<T> IntoIterator for Grid<T> {
     type IntoIter = std::iter::Zip<PositionIterator, std::vec::IntoIter<T>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.size.into_iter().zip(self.grid.into_iter())
+        self.size.into_iter().zip(self.grid)
     }
 }
 
@@ -1,10 +1,6 @@
 #![warn(missing_debug_implementations)]
 
-use std::{
-    collections::{hash_set, HashSet},
-    fs,
-    str::FromStr,
-};
+use std::{fs, str::FromStr};
 
 mod grid;
 use grid::*;
@@ -79,8 +75,30 @@ impl FromStr for Input {
     }
 }
 
+// Sum of |a - b| over every pair, each empty line before a coordinate counting `lines_per_empty_line` times.
+fn axis_distance_sum(
+    mut coordinates: Vec<usize>,
+    empty: &[usize],
+    lines_per_empty_line: usize,
+) -> usize {
+    coordinates.sort_unstable();
+    let mut sum = 0;
+    let mut prefix = 0;
+    for (i, coordinate) in coordinates.into_iter().enumerate() {
+        // `empty` is sorted already, it came out of `empty_indices` in order.
+        // Never more empty lines before a coordinate than the coordinate itself, so this can't go below zero.
+        let empty_before = empty.partition_point(|x| *x < coordinate);
+        let expanded = coordinate - empty_before + empty_before * lines_per_empty_line;
+        sum += expanded * i - prefix;
+        prefix += expanded;
+    }
+    sum
+}
+
 impl Input {
-    fn solve(&self) -> usize {
+    // How many lines every empty one turns into: 2 for the first star, 1000000 for the second.
+    // 1 leaves the map as it is, 0 takes the empty lines out.
+    fn solve(&self, lines_per_empty_line: usize) -> usize {
         let horizontal_empty_indices = empty_indices((0..self.grid.size().0).map(move |x| {
             (0..self.grid.size().1).map(move |y| *self.grid.get(Position(x, y)).unwrap())
         }));
@@ -90,37 +108,19 @@ impl Input {
         }));
 
         let positions = galaxy_positions(&self.grid);
-        let mut distance_total = 0;
-        let mut millions_to_be_added = 0;
-        for i in 0..(positions.len() - 1) {
-            for j in (i + 1)..positions.len() {
-                dbg!(i, j);
-                distance_total += Position::distance(positions[i], positions[j]);
-
-                let horizontal_min = std::cmp::min(positions[i].0, positions[j].0);
-                let horizontal_max = std::cmp::max(positions[i].0, positions[j].0);
-
-                let vertical_min = std::cmp::min(positions[i].1, positions[j].1);
-                let vertical_max = std::cmp::max(positions[i].1, positions[j].1);
-
-                millions_to_be_added += horizontal_empty_indices.iter().fold(0, |init, x| {
-                    match (horizontal_min..horizontal_max).contains(x) {
-                        true => init + 1,
-                        false => init,
-                    }
-                }) + vertical_empty_indices.iter().fold(0, |init, y| {
-                    match (vertical_min..vertical_max).contains(y) {
-                        true => init + 1,
-                        false => init,
-                    }
-                });
-            }
-        }
-        distance_total + millions_to_be_added * 999999
+        axis_distance_sum(
+            positions.iter().map(|x| x.0).collect(),
+            &horizontal_empty_indices,
+            lines_per_empty_line,
+        ) + axis_distance_sum(
+            positions.iter().map(|x| x.1).collect(),
+            &vertical_empty_indices,
+            lines_per_empty_line,
+        )
     }
 }
 
 fn main() {
     let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
-    dbg!(&input.solve());
+    dbg!(input.solve(2), input.solve(1000000));
 }
@@ -2,6 +2,9 @@
 
 use std::{fs, str::FromStr};
 
+mod memo;
+use memo::*;
+
 #[derive(Debug)]
 struct ParseError {}
 
@@ -51,64 +54,39 @@ impl FromStr for Row {
 }
 
 impl Row {
-    fn empty_states(&self) -> Vec<Vec<usize>> {
-        let mut states: Vec<Vec<usize>> = vec![];
-        states.resize(self.pattern.len(), vec![]);
-        for i in 0..states.len() {
-            states[i].resize(self.pattern[i] + 1, 0);
-        }
-        states.push(vec![0]);
-        states
+    fn key(&self, spring: usize, group: usize) -> usize {
+        spring * (self.pattern.len() + 1) + group
     }
 
-    fn broken_states(&self, states: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-        let mut new_states = self.empty_states();
-        for i in 0..states.len() {
-            for j in 0..(states[i].len() - 1) {
-                new_states[i][j + 1] = states[i][j];
+    // Ways to fill springs[spring..] with pattern[group..].
+    fn count(&self, memo: &mut Memo<Dense<usize>>, spring: usize, group: usize) -> usize {
+        memo.call(self.key(spring, group), |memo, _| {
+            if spring == self.springs.len() {
+                return (group == self.pattern.len()) as usize;
             }
-        }
-        new_states
-    }
 
-    fn normal_states(&self, states: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-        let mut new_states = self.empty_states();
-        for i in 0..states.len() {
-            new_states[i][0] = states[i][0];
-            if i != 0 {
-                new_states[i][0] += states[i - 1][states[i - 1].len() - 1];
+            let mut total = 0;
+            if self.springs[spring] != Some(true) {
+                total += self.count(memo, spring + 1, group);
             }
-        }
-        new_states
-    }
-
-    fn unknown_states(&self, states: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-        let broken = self.broken_states(states);
-        let normal = self.normal_states(states);
-        let mut new_states = self.empty_states();
-        for i in 0..new_states.len() {
-            for j in 0..new_states[i].len() {
-                new_states[i][j] = broken[i][j] + normal[i][j];
+            if self.springs[spring] != Some(false) && group < self.pattern.len() {
+                let end = spring + self.pattern[group];
+                if end <= self.springs.len()
+                    && self.springs[spring..end].iter().all(|x| *x != Some(false))
+                    && self.springs.get(end) != Some(&Some(true))
+                {
+                    // Hop over the normal spring that has to follow the group too.
+                    let next = (end + 1).min(self.springs.len());
+                    total += self.count(memo, next, group + 1);
+                }
             }
-        }
-        new_states
+            total
+        })
     }
 
-    fn solve(&self) -> usize {
-        let mut states = self.empty_states();
-
-        states[0][0] = 1;
-
-        for spring in &self.springs {
-            states = match spring {
-                Some(true) => self.broken_states(&states),
-                Some(false) => self.normal_states(&states),
-                None => self.unknown_states(&states),
-            };
-        }
-        let states_len = states.len();
-        let last_segment_len = states[states_len - 2].len();
-        states[states_len - 2][last_segment_len - 1] + states[states_len - 1][0]
+    fn solve(&self) -> (usize, Stats) {
+        let mut memo = Memo::dense(self.key(self.springs.len(), self.pattern.len()) + 1);
+        (self.count(&mut memo, 0, 0), memo.stats())
     }
 }
 
@@ -130,12 +108,21 @@ impl FromStr for Input {
 }
 
 impl Input {
-    fn solve(&self) -> usize {
-        self.rows.iter().fold(0, |init, x| init + x.solve())
+    fn solve(&self) -> (usize, Stats) {
+        let mut total_stats = Stats::default();
+        let mut total = 0;
+        for row in &self.rows {
+            let (count, stats) = row.solve();
+            total += count;
+            total_stats += stats;
+        }
+        (total, total_stats)
     }
 }
 
 fn main() {
     let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
-    dbg!(input.solve());
+    let (total, stats) = input.solve();
+    dbg!(stats, stats.hit_rate());
+    dbg!(total);
 }
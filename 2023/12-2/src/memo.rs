@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub trait Cache {
+    type Key;
+    type Value;
+
+    fn lookup(&self, key: &Self::Key) -> Option<&Self::Value>;
+    fn store(&mut self, key: Self::Key, value: Self::Value);
+}
+
+impl<K: Hash + Eq, V> Cache for HashMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn lookup(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn store(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+// For when the keys are small indices anyway, no hashing needed.
+#[derive(Debug)]
+pub struct Dense<V> {
+    table: Vec<Option<V>>,
+}
+
+impl<V> Dense<V> {
+    pub fn with_size(size: usize) -> Self {
+        let mut table = Vec::with_capacity(size);
+        table.resize_with(size, || None);
+        Dense { table }
+    }
+}
+
+impl<V> Cache for Dense<V> {
+    type Key = usize;
+    type Value = V;
+
+    fn lookup(&self, key: &usize) -> Option<&V> {
+        self.table.get(*key)?.as_ref()
+    }
+
+    fn store(&mut self, key: usize, value: V) {
+        self.table[key] = Some(value);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Stats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.hits += rhs.hits;
+        self.misses += rhs.misses;
+    }
+}
+
+// Write the solver as plain recursion that goes through `call` for every sub-problem:
+//
+//     fn count(memo: &mut Memo<HashMap<usize, usize>>, n: usize) -> usize {
+//         memo.call(n, |memo, n| if n < 2 { n } else { count(memo, n - 1) + count(memo, n - 2) })
+//     }
+#[derive(Debug)]
+pub struct Memo<C> {
+    cache: C,
+    stats: Stats,
+}
+
+impl<K: Hash + Eq, V> Memo<HashMap<K, V>> {
+    pub fn hashed() -> Self {
+        Memo::new(HashMap::new())
+    }
+}
+
+impl<V> Memo<Dense<V>> {
+    pub fn dense(size: usize) -> Self {
+        Memo::new(Dense::with_size(size))
+    }
+}
+
+impl<C> Memo<C> {
+    pub fn new(cache: C) -> Self {
+        Memo {
+            cache,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl<C: Cache> Memo<C>
+where
+    C::Key: Clone,
+    C::Value: Clone,
+{
+    pub fn call<F>(&mut self, key: C::Key, f: F) -> C::Value
+    where
+        F: FnOnce(&mut Self, C::Key) -> C::Value,
+    {
+        if let Some(value) = self.cache.lookup(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = f(self, key.clone());
+        self.cache.store(key, value.clone());
+        value
+    }
+}
@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// The sequence initial, step(initial), step(step(initial)), ... looks like a rho:
+// `tail` states before the loop starts, then the same `period` states forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub tail: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    // Which earlier step the n-th step is identical to.
+    pub fn index_at(&self, n: usize) -> usize {
+        if n < self.tail {
+            n
+        } else {
+            self.tail + (n - self.tail) % self.period
+        }
+    }
+
+    pub fn state_at<S, F>(&self, initial: S, mut step: F, n: usize) -> S
+    where
+        F: FnMut(&S) -> S,
+    {
+        let mut state = initial;
+        for _ in 0..self.index_at(n) {
+            state = step(&state);
+        }
+        state
+    }
+}
+
+// Brent's algorithm. Only ever keeps two states around.
+pub fn find_cycle<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut tail = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+    }
+
+    Cycle { tail, period }
+}
+
+fn fingerprint<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Remembers a hash of every state instead of the state itself.
+// A fingerprint hit is double checked by replaying from `initial`, so collisions can't lie.
+pub fn find_cycle_hashed<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + Hash + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut state = initial.clone();
+    let mut index = 0;
+    loop {
+        let candidates = seen.entry(fingerprint(&state)).or_default();
+        for &candidate in candidates.iter() {
+            let mut earlier = initial.clone();
+            for _ in 0..candidate {
+                earlier = step(&earlier);
+            }
+            if earlier == state {
+                return Cycle {
+                    tail: candidate,
+                    period: index - candidate,
+                };
+            }
+        }
+        candidates.push(index);
+
+        state = step(&state);
+        index += 1;
+    }
+}
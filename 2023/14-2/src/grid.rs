@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use std::{iter::FusedIterator, ops::Add};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -101,7 +103,7 @@ impl<T> Grid<T> {
     }
 
     pub fn with_vec(vec: Vec<T>, width: usize) -> Option<Grid<T>> {
-        if vec.len() % width == 0 {
+        if vec.len().is_multiple_of(width) {
             let size = Position(width, vec.len() / width);
             Some(Self { grid: vec, size })
         } else {
@@ -141,7 +143,7 @@ impl<T> IntoIterator for Grid<T> {
     type IntoIter = std::iter::Zip<PositionIterator, std::vec::IntoIter<T>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.size.into_iter().zip(self.grid.into_iter())
+        self.size.into_iter().zip(self.grid)
     }
 }
 
@@ -1,10 +1,11 @@
 #![warn(missing_debug_implementations)]
 
-use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
 
+mod cycle;
 mod grid;
+use cycle::*;
 use grid::*;
 
 #[derive(Debug)]
@@ -17,7 +18,7 @@ enum Tile {
     Empty,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Input {
     grid: Grid<Tile>,
 }
@@ -31,7 +32,7 @@ impl FromStr for Input {
         for (i, c) in s.trim().chars().enumerate() {
             match c {
                 '\n' => {
-                    if width == None {
+                    if width.is_none() {
                         width = Some(i);
                     }
                 }
@@ -165,6 +166,7 @@ impl Input {
         total_load
     }
 
+    #[allow(dead_code)]
     fn print(&self) {
         for y in 0..self.grid.size().1 {
             for x in 0..self.grid.size().1 {
@@ -182,32 +184,24 @@ impl Input {
         println!();
     }
 
-    fn solve(&mut self) -> usize {
-        let mut hash_map: HashMap<Grid<Tile>, usize> = HashMap::new();
-        let mut history = vec![];
+    fn spin_cycle(&self) -> Input {
+        let mut next = self.clone();
+        next.tilt_north();
+        next.tilt_west();
+        next.tilt_south();
+        next.tilt_east();
+        next
+    }
 
+    fn solve(&self) -> usize {
         // whatever this number is—a billion?
-        for cycle_count in 0..1_000_000_000 {
-            history.push(self.grid.clone());
-            if let Some(before_cycle) = hash_map.get(&self.grid) {
-                let cycle_distance = cycle_count - before_cycle;
-                let cycles_left = 1_000_000_000 - cycle_count;
-                let offset = cycles_left % cycle_distance;
-                return Self::load(&history[cycle_count - cycle_distance + offset]);
-            }
-            hash_map.insert(self.grid.clone(), cycle_count);
-
-            self.tilt_north();
-            self.tilt_west();
-            self.tilt_south();
-            self.tilt_east();
-        }
-
-        Self::load(&self.grid)
+        let cycle = find_cycle(self.clone(), Input::spin_cycle);
+        let spun = cycle.state_at(self.clone(), Input::spin_cycle, 1_000_000_000);
+        Self::load(&spun.grid)
     }
 }
 
 fn main() {
-    let mut input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
+    let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
     dbg!(input.solve());
 }
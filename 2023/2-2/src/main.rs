@@ -1,72 +1,172 @@
 #![warn(missing_debug_implementations)]
 
-use enum_map::enum_map;
-use enum_map::Enum;
-use enum_map::EnumMap;
-use std::cmp::max;
+use std::collections::BTreeMap;
 use std::fs;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, Debug, Enum)]
-enum Color {
-    Red,
-    Green,
-    Blue,
+mod parse;
+use parse::*;
+
+// Colors aren't fixed, whatever shows up in the input is a color.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Cubes {
+    counts: BTreeMap<String, u32>,
 }
 
-fn parse_id(s: &str) -> u32 {
-    s[5..].parse().unwrap()
+type Draw = Cubes;
+type Bag = Cubes;
+
+impl Cubes {
+    fn new(counts: &[(&str, u32)]) -> Self {
+        Cubes {
+            counts: counts.iter().map(|(c, n)| (c.to_string(), *n)).collect(),
+        }
+    }
+
+    fn count(&self, color: &str) -> u32 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    // Over every color that's in there, whatever they're called.
+    fn power(&self) -> u32 {
+        self.counts.values().product()
+    }
 }
 
-fn parse_color(s: &str) -> Color {
-    match s {
-        "red" => Color::Red,
-        "green" => Color::Green,
-        "blue" => Color::Blue,
-        _ => panic!(),
+impl FromText for Cubes {
+    fn from_text(text: Text) -> Result<Self, ParseError> {
+        let mut counts = BTreeMap::new();
+        for cubes in text.split(",") {
+            let (count, color) = cubes.trim().split_once(" ")?;
+            let color = color.trim();
+            if counts
+                .insert(color.as_str().to_string(), count.parse()?)
+                .is_some()
+            {
+                return Err(color.error("each color once per draw"));
+            }
+        }
+        Ok(Cubes { counts })
     }
 }
 
-fn parse_ball_color(s: &str) -> (Color, u32) {
-    let splitted: Vec<&str> = s.split(' ').collect();
-    (parse_color(splitted[1]), splitted[0].parse().unwrap())
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Failure {
+    draw_index: usize,
+    color: String,
+    drawn: u32,
+    available: u32,
 }
 
-fn parse_ball_set(s: &str) -> EnumMap<Color, u32> {
-    let mut results: EnumMap<Color, u32> = enum_map!(_ => 0);
-    for balls in s.split(", ") {
-        let (color, value) = parse_ball_color(balls);
-        results[color] = value;
+#[derive(Debug)]
+struct Game {
+    id: u32,
+    draws: Vec<Draw>,
+}
+
+impl FromText for Game {
+    fn from_text(text: Text) -> Result<Self, ParseError> {
+        let (id, draws) = text.key_value()?;
+        Ok(Game {
+            id: id.tag("Game ")?.parse()?,
+            draws: draws
+                .split(";")
+                .map(|x| x.read())
+                .collect::<Result<_, _>>()?,
+        })
     }
-    results
 }
 
-fn parse_ball_set_set(s: &str) -> u32 {
-    let mut results: EnumMap<Color, u32> = enum_map!(_ => 0);
-    for set in s.split("; ") {
-        let set_result = parse_ball_set(set);
-        for (color, value) in set_result {
-            results[color] = max(results[color], value);
+impl Game {
+    // The first draw that needs more of some color than the bag has.
+    fn first_failure(&self, bag: &Bag) -> Option<Failure> {
+        self.draws
+            .iter()
+            .enumerate()
+            .find_map(|(draw_index, draw)| {
+                draw.counts
+                    .iter()
+                    .find(|(color, drawn)| **drawn > bag.count(color))
+                    .map(|(color, drawn)| Failure {
+                        draw_index,
+                        color: color.clone(),
+                        drawn: *drawn,
+                        available: bag.count(color),
+                    })
+            })
+    }
+
+    fn is_possible(&self, bag: &Bag) -> bool {
+        self.first_failure(bag).is_none()
+    }
+
+    fn minimal_bag(&self) -> Bag {
+        let mut bag = Bag::default();
+        for draw in &self.draws {
+            for (color, drawn) in &draw.counts {
+                let count = bag.counts.entry(color.clone()).or_insert(0);
+                *count = (*count).max(*drawn);
+            }
         }
+        bag
     }
+}
+
+#[derive(Debug)]
+struct Input {
+    games: Vec<Game>,
+}
 
-    println!("{}", results.iter().fold(1, |x, (_, y)| x * y));
-    results.iter().fold(1, |x, (_, y)| x * y)
+impl FromStr for Input {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Input {
+            games: Text::new(s)
+                .lines()
+                .map(|x| x.read())
+                .collect::<Result<_, _>>()?,
+        })
+    }
 }
 
-fn parse_line(s: &str) -> (u32, u32) {
-    let splitted: Vec<&str> = s.split(": ").collect();
-    (parse_id(splitted[0]), parse_ball_set_set(splitted[1]))
+impl Input {
+    fn failures(&self, bag: &Bag) -> Vec<(u32, Failure)> {
+        self.games
+            .iter()
+            .filter_map(|x| Some((x.id, x.first_failure(bag)?)))
+            .collect()
+    }
 }
 
 fn main() {
-    let input = fs::read_to_string("input").unwrap();
-    let mut sum = 0;
-    for l in input.split("\n") {
-        if l == "" {
-            continue;
+    let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
+    let bag = Bag::new(&[("red", 12), ("green", 13), ("blue", 14)]);
+
+    // `--failures` lists why the impossible games are impossible.
+    if std::env::args().any(|x| x == "--failures") {
+        for (id, failure) in input.failures(&bag) {
+            println!(
+                "game {}, draw {}: {} {} but only {} in the bag",
+                id,
+                failure.draw_index + 1,
+                failure.drawn,
+                failure.color,
+                failure.available
+            );
         }
-        let (_, value) = parse_line(l);
-        sum += value;
     }
-    println!("{}", sum);
+
+    // First gold star
+    let possible_sum: u32 = input
+        .games
+        .iter()
+        .filter(|x| x.is_possible(&bag))
+        .map(|x| x.id)
+        .sum();
+    println!("{}", possible_sum);
+
+    // Second gold star
+    let power_sum: u32 = input.games.iter().map(|x| x.minimal_bag().power()).sum();
+    println!("{}", power_sum);
 }
@@ -1,19 +1,24 @@
 #![warn(missing_debug_implementations)]
 
 use std::fs;
+use std::ops::Range;
+use std::str::FromStr;
 
-const WIDTH: i32 = 140;
-const HEIGHT: i32 = WIDTH;
-
-const ADJACENT: [(i32, i32); 8] = [
-    (-1, -1),
-    (0, -1),
-    (1, -1),
-    (-1, 0),
-    (1, 0),
-    (-1, 1),
-    (0, 1),
-    (1, 1),
+mod grid;
+use grid::*;
+
+#[derive(Debug)]
+struct ParseError {}
+
+const ADJACENT: [Vector; 8] = [
+    Vector(-1, -1),
+    Vector(0, -1),
+    Vector(1, -1),
+    Vector(-1, 0),
+    Vector(1, 0),
+    Vector(-1, 1),
+    Vector(0, 1),
+    Vector(1, 1),
 ];
 
 #[derive(Debug, Clone, Copy)]
@@ -23,124 +28,140 @@ enum Cell {
     Numeral(u32),
 }
 
-#[derive(Debug, Clone, Copy)]
-enum GearCell {
-    None,
-    Gear,
-    Number(u32, usize),
+#[derive(Debug)]
+struct Number {
+    id: usize,
+    value: u32,
+    y: usize,
+    xs: Range<usize>,
 }
 
-fn position_inside(x: i32, y: i32) -> bool {
-    x >= 0 && x < HEIGHT && y >= 0 && y < HEIGHT
+#[derive(Debug)]
+struct Symbol {
+    kind: char,
+    position: Position,
 }
 
-fn main() {
-    let input = fs::read_to_string("input").unwrap();
-    let mut matrix = [[Cell::None; WIDTH as usize]; HEIGHT as usize];
-
-    for (y, l) in input.split('\n').enumerate() {
-        for (x, c) in l.chars().enumerate() {
-            matrix[y][x] = match c {
-                '.' => Cell::None,
-                '0' => Cell::Numeral(0),
-                '1' => Cell::Numeral(1),
-                '2' => Cell::Numeral(2),
-                '3' => Cell::Numeral(3),
-                '4' => Cell::Numeral(4),
-                '5' => Cell::Numeral(5),
-                '6' => Cell::Numeral(6),
-                '7' => Cell::Numeral(7),
-                '8' => Cell::Numeral(8),
-                '9' => Cell::Numeral(9),
-                a => Cell::Symbol(a),
+#[derive(Debug)]
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // Which number, by id, covers each cell.
+    number_ids: Grid<Option<usize>>,
+}
+
+impl FromStr for Schematic {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cells = vec![];
+        let mut width = None;
+        for l in s.lines().filter(|x| !x.is_empty()) {
+            if *width.get_or_insert(l.chars().count()) != l.chars().count() {
+                return Err(ParseError {});
+            }
+            for c in l.chars() {
+                cells.push(match c {
+                    '.' => Cell::None,
+                    c if c.is_ascii_digit() => Cell::Numeral(c.to_digit(10).unwrap()),
+                    c => Cell::Symbol(c),
+                });
             }
         }
-    }
+        let grid = Grid::with_vec(cells, width.ok_or(ParseError {})?).ok_or(ParseError {})?;
 
-    let mut id: usize = 0;
-    let mut gear_matrix = [[GearCell::None; WIDTH as usize]; HEIGHT as usize];
-
-    for y in 0..HEIGHT {
-        let mut numerals: Vec<u32> = Vec::new();
-        for x in 0..WIDTH {
-            if let Cell::Numeral(n) = matrix[y as usize][x as usize] {
-                numerals.push(n);
-            } else {
-                if let Cell::Symbol('*') = matrix[y as usize][x as usize] {
-                    gear_matrix[y as usize][x as usize] = GearCell::Gear;
-                }
-                if !numerals.is_empty() {
-                    let mut number = 0;
-                    for n in &numerals {
-                        number = number * 10 + n;
-                    }
-                    let number = number;
-
-                    for i in 0..numerals.len() {
-                        gear_matrix[y as usize][(x - (1 + i as i32)) as usize] =
-                            GearCell::Number(number, id);
+        let mut numbers: Vec<Number> = vec![];
+        let mut symbols = vec![];
+        let mut number_ids = Grid::with(grid.size(), |_| None);
+        for (position, cell) in &grid {
+            match *cell {
+                Cell::Numeral(n) => {
+                    let Position(x, y) = position;
+                    match numbers.last_mut() {
+                        // Still going on the same number
+                        Some(number) if number.y == y && number.xs.end == x => {
+                            number.value = number.value * 10 + n;
+                            number.xs.end += 1;
+                        }
+                        _ => numbers.push(Number {
+                            id: numbers.len(),
+                            value: n,
+                            y,
+                            xs: x..x + 1,
+                        }),
                     }
-                    id += 1;
-                    numerals = vec![];
+                    number_ids.set(position, Some(numbers.len() - 1)).unwrap();
                 }
+                Cell::Symbol(kind) => symbols.push(Symbol { kind, position }),
+                Cell::None => (),
             }
         }
 
-        let mut number = 0;
-        for n in &numerals {
-            number = number * 10 + n;
-        }
-        let number = number;
-        for i in 0..numerals.len() {
-            gear_matrix[y as usize][(WIDTH - (1 + i as i32)) as usize] =
-                GearCell::Number(number, id);
-        }
-        id += 1;
+        Ok(Schematic {
+            numbers,
+            symbols,
+            number_ids,
+        })
     }
+}
 
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            match gear_matrix[y as usize][x as usize] {
-                GearCell::None => print!("."),
-                GearCell::Gear => print!("*"),
-                a => {
-                    dbg!(a);
-                    ()
-                }
-            }
-        }
+impl Schematic {
+    fn neighbors(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ADJACENT
+            .into_iter()
+            .filter_map(move |x| position.checked_add_signed(x))
+            .filter(|x| x.within(self.number_ids.size()))
     }
 
-    let mut sum = 0;
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            if let GearCell::Gear = gear_matrix[y as usize][x as usize] {
-                let mut nearby = [0; 2];
-                let mut index = 0;
-                let mut previous_id = None;
-                let mut success = true;
-                for (dx, dy) in ADJACENT {
-                    let (nx, ny) = (dx + x, dy + y);
-                    if position_inside(nx, ny) {
-                        if let GearCell::Number(number, id) = gear_matrix[ny as usize][nx as usize]
-                        {
-                            if previous_id == None || previous_id.unwrap() < id {
-                                previous_id = Some(id);
-                                nearby[index] = number;
-                                index += 1;
-                                if index >= 2 {
-                                    success = true;
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-                if success {
-                    sum += nearby[0] * nearby[1];
-                }
-            }
-        }
+    fn numbers_adjacent_to(&self, symbol: &Symbol) -> Vec<&Number> {
+        let mut numbers: Vec<&Number> = self
+            .neighbors(symbol.position)
+            .filter_map(|x| *self.number_ids.get(x).unwrap())
+            .map(|x| &self.numbers[x])
+            .collect();
+        // One number can touch the symbol from a couple of cells.
+        numbers.sort_by_key(|x| x.id);
+        numbers.dedup_by_key(|x| x.id);
+        numbers
     }
-    println!("{}", sum);
+
+    fn symbols_adjacent_to(&self, number: &Number) -> Vec<&Symbol> {
+        self.symbols
+            .iter()
+            .filter(|symbol| {
+                let Position(x, y) = symbol.position;
+                y.abs_diff(number.y) <= 1 && x + 1 >= number.xs.start && x <= number.xs.end
+            })
+            .collect()
+    }
+
+    fn symbols_with_neighbors(&self, kind: char, count: usize) -> Vec<(&Symbol, Vec<&Number>)> {
+        self.symbols
+            .iter()
+            .filter(|x| x.kind == kind)
+            .map(|x| (x, self.numbers_adjacent_to(x)))
+            .filter(|(_, numbers)| numbers.len() == count)
+            .collect()
+    }
+}
+
+fn main() {
+    let schematic: Schematic = fs::read_to_string("input").unwrap().parse().unwrap();
+
+    // First gold star
+    let part_sum: u32 = schematic
+        .numbers
+        .iter()
+        .filter(|x| !schematic.symbols_adjacent_to(x).is_empty())
+        .map(|x| x.value)
+        .sum();
+    println!("{}", part_sum);
+
+    // Second gold star
+    let gear_ratio_sum: u32 = schematic
+        .symbols_with_neighbors('*', 2)
+        .iter()
+        .map(|(_, numbers)| numbers.iter().map(|x| x.value).product::<u32>())
+        .sum();
+    println!("{}", gear_ratio_sum);
 }
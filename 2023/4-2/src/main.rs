@@ -1,45 +1,115 @@
-#[warn(missing_debug_implementations)]
+#![warn(missing_debug_implementations)]
+use std::collections::HashSet;
 use std::fs;
 
-fn main() {
-    let input = fs::read_to_string("input").unwrap();
+mod parse;
+use parse::*;
+
+#[derive(Debug)]
+struct Scratchcard {
+    id: usize,
+    winning_numbers: HashSet<u32>,
+    posessed_numbers: Vec<u32>,
+}
+
+impl FromText for Scratchcard {
+    fn from_text(text: Text) -> Result<Self, ParseError> {
+        let (id, numbers) = text.key_value()?;
+        let (winning, posessed) = numbers.split_once("|")?;
+        Ok(Scratchcard {
+            id: id.tag("Card")?.parse()?,
+            winning_numbers: winning.list(" ")?.into_iter().collect(),
+            posessed_numbers: posessed.list(" ")?,
+        })
+    }
+}
 
-    let mut counts = vec![];
+impl Scratchcard {
+    fn matches(&self) -> usize {
+        self.posessed_numbers
+            .iter()
+            .filter(|x| self.winning_numbers.contains(x))
+            .count()
+    }
 
-    for line in input.split('\n') {
-        if line == "" {
-            continue;
+    fn points(&self) -> u64 {
+        match self.matches() {
+            0 => 0,
+            n => 1 << (n - 1),
         }
-        let number_line = line.split(": ").collect::<Vec<_>>()[1]
-            .split(" | ")
-            .collect::<Vec<_>>();
-        let posessed_numbers: Vec<u32> = number_line[1]
-            .split_whitespace()
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect();
-        let winning_numbers: Vec<u32> = number_line[0]
-            .split_whitespace()
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect();
-        let mut count = 0;
-        for i in &posessed_numbers {
-            for j in &winning_numbers {
-                if i == j {
-                    count += 1;
-                    break;
-                }
+    }
+}
+
+// What to do when a card wins copies of cards past the end of the table.
+#[derive(Debug, Clone, Copy)]
+enum Overflow {
+    // The puzzle promises this never happens, so treat it as broken input.
+    Error,
+    // Cards that don't exist can't be copied.
+    Drop,
+}
+
+#[derive(Debug)]
+struct OverflowError {
+    card_id: usize,
+    matches: usize,
+}
+
+#[derive(Debug)]
+struct Report {
+    // (card id, how many of it we end up with)
+    copies: Vec<(usize, u64)>,
+    total_copies: u64,
+    points: u64,
+}
+
+fn cascade(scratchcards: &[Scratchcard], overflow: Overflow) -> Result<Report, OverflowError> {
+    let mut copies = vec![1; scratchcards.len()];
+
+    for (i, scratchcard) in scratchcards.iter().enumerate() {
+        let matches = scratchcard.matches();
+        let end = i + 1 + matches;
+        if end > scratchcards.len() {
+            if let Overflow::Error = overflow {
+                return Err(OverflowError {
+                    card_id: scratchcard.id,
+                    matches,
+                });
             }
         }
-        counts.push(count);
+        for j in (i + 1)..end.min(scratchcards.len()) {
+            copies[j] += copies[i];
+        }
     }
 
-    let mut cards = counts.iter().map(|_| 1).collect::<Vec<_>>();
+    Ok(Report {
+        total_copies: copies.iter().sum(),
+        points: scratchcards.iter().map(|x| x.points()).sum(),
+        copies: scratchcards.iter().map(|x| x.id).zip(copies).collect(),
+    })
+}
+
+fn main() {
+    let input = fs::read_to_string("input").unwrap();
+    let scratchcards: Vec<Scratchcard> = Text::new(&input)
+        .lines()
+        .map(|x| x.read())
+        .collect::<Result<_, _>>()
+        .unwrap();
 
-    for i in 0..counts.len() {
-        for j in 0..counts[i] {
-            cards[i + j + 1] += cards[i];
+    let report = match cascade(&scratchcards, Overflow::Error) {
+        Ok(report) => report,
+        Err(OverflowError { card_id, matches }) => {
+            eprintln!(
+                "card {} wins {} cards, that's past the end. Dropping those.",
+                card_id, matches
+            );
+            cascade(&scratchcards, Overflow::Drop).unwrap()
         }
+    };
+    for (id, copies) in &report.copies {
+        println!("Card {}: {}", id, copies);
     }
-
-    dbg!(cards.iter().fold(0, |a, x| a + x));
+    dbg!(report.points);
+    dbg!(report.total_copies);
 }
@@ -0,0 +1,195 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::str::FromStr;
+
+// Lines and columns count from 1, like an editor does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: expected {}, found {:?}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub trait FromText: Sized {
+    fn from_text(text: Text) -> Result<Self, ParseError>;
+}
+
+// A piece of the input that still knows where it came from, so errors can point at it.
+#[derive(Debug, Clone, Copy)]
+pub struct Text<'a> {
+    source: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Text<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Text {
+            source,
+            start: 0,
+            end: source.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        &self.source[self.start..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    // `s` has to be a slice of `self.as_str()`.
+    fn sub(&self, s: &'a str) -> Text<'a> {
+        let start = s.as_ptr() as usize - self.source.as_ptr() as usize;
+        Text {
+            source: self.source,
+            start,
+            end: start + s.len(),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.source[..self.start].matches('\n').count() + 1
+    }
+
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let before = &self.source[..self.start];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        ParseError {
+            line: self.line(),
+            column: before[line_start..].chars().count() + 1,
+            expected: expected.into(),
+            found: self.as_str().lines().next().unwrap_or("").to_string(),
+        }
+    }
+
+    pub fn trim(&self) -> Text<'a> {
+        self.sub(self.as_str().trim())
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Text<'a>> + '_ {
+        self.as_str()
+            .lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| self.sub(x))
+    }
+
+    // Blocks separated by blank lines.
+    pub fn sections(&self) -> impl Iterator<Item = Text<'a>> + '_ {
+        self.as_str()
+            .split("\n\n")
+            .map(|x| x.trim_matches('\n'))
+            .filter(|x| !x.is_empty())
+            .map(|x| self.sub(x))
+    }
+
+    pub fn split(&self, separator: &'a str) -> impl Iterator<Item = Text<'a>> + '_ {
+        self.as_str().split(separator).map(|x| self.sub(x))
+    }
+
+    pub fn split_once(&self, separator: &str) -> Result<(Text<'a>, Text<'a>), ParseError> {
+        let (left, right) = self
+            .as_str()
+            .split_once(separator)
+            .ok_or_else(|| self.error(format!("{:?}", separator)))?;
+        Ok((self.sub(left), self.sub(right)))
+    }
+
+    // `key: value`
+    pub fn key_value(&self) -> Result<(Text<'a>, Text<'a>), ParseError> {
+        let (key, value) = self.split_once(":")?;
+        Ok((key.trim(), value.trim()))
+    }
+
+    // Eats a fixed prefix and hands back the rest.
+    pub fn tag(&self, tag: &str) -> Result<Text<'a>, ParseError> {
+        self.as_str()
+            .strip_prefix(tag)
+            .map(|x| self.sub(x))
+            .ok_or_else(|| self.error(format!("{:?}", tag)))
+    }
+
+    // Splits off a leading run of letters, digits and underscores.
+    pub fn identifier(&self) -> Result<(Text<'a>, Text<'a>), ParseError> {
+        let s = self.as_str();
+        let length = s
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(s.len());
+        if length == 0 {
+            return Err(self.error("identifier"));
+        }
+        Ok((self.sub(&s[..length]), self.sub(&s[length..])))
+    }
+
+    // `(A, B)`
+    pub fn tuple(&self) -> Result<(Text<'a>, Text<'a>), ParseError> {
+        let text = self.trim();
+        let inner = text.tag("(")?;
+        let inner = inner.sub(
+            inner
+                .as_str()
+                .strip_suffix(')')
+                .ok_or_else(|| text.error("\")\" at the end"))?,
+        );
+        let (left, right) = inner.split_once(",")?;
+        Ok((left.trim(), right.trim()))
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError> {
+        let type_name = std::any::type_name::<T>();
+        self.as_str().trim().parse().map_err(|_| {
+            self.trim()
+                .error(type_name.rsplit("::").next().unwrap_or(type_name))
+        })
+    }
+
+    pub fn read<T: FromText>(&self) -> Result<T, ParseError> {
+        T::from_text(*self)
+    }
+
+    // Tokens split on any of the `separators`, empty tokens are skipped.
+    pub fn list<T: FromStr>(&self, separators: &str) -> Result<Vec<T>, ParseError> {
+        self.as_str()
+            .split(|c| separators.contains(c))
+            .filter(|x| !x.is_empty())
+            .map(|x| self.sub(x).parse())
+            .collect()
+    }
+
+    pub fn chars_map<T, F>(&self, expected: &str, f: F) -> Result<Vec<T>, ParseError>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        self.as_str()
+            .char_indices()
+            .map(|(i, c)| f(c).ok_or_else(|| self.sub(&self.as_str()[i..]).error(expected)))
+            .collect()
+    }
+
+    pub fn one_of<T: Copy>(&self, options: &[(&str, T)]) -> Result<T, ParseError> {
+        let text = self.trim();
+        options
+            .iter()
+            .find(|(name, _)| *name == text.as_str())
+            .map(|(_, t)| *t)
+            .ok_or_else(|| {
+                let names: Vec<_> = options.iter().map(|(name, _)| *name).collect();
+                text.error(format!("one of {}", names.join(", ")))
+            })
+    }
+}
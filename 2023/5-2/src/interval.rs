@@ -0,0 +1,308 @@
+#![allow(dead_code)]
+
+use std::cmp::{max, min};
+use std::fmt::Debug;
+use std::ops::{Add, Range, Sub};
+
+pub trait Integer: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {})*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+fn intersect<T: Integer>(a: &Range<T>, b: &Range<T>) -> Option<Range<T>> {
+    let range = max(a.start, b.start)..min(a.end, b.end);
+    if range.is_empty() {
+        None
+    } else {
+        Some(range)
+    }
+}
+
+// Always kept sorted, non-empty, and with no two ranges touching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+
+    fn normalize(mut ranges: Vec<Range<T>>) -> Self {
+        ranges.retain(|x| !x.is_empty());
+        ranges.sort_by_key(|x| x.start);
+
+        let mut normalized: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match normalized.last_mut() {
+                Some(last) if range.start <= last.end => last.end = max(last.end, range.end),
+                _ => normalized.push(range),
+            }
+        }
+        IntervalSet { ranges: normalized }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|x| x.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|x| x.end <= value);
+        self.ranges.get(index).is_some_and(|x| x.contains(&value))
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = Self::normalize(ranges);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalize(self.ranges.iter().chain(&other.ranges).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            if let Some(range) = intersect(a, b) {
+                ranges.push(range);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            // Don't advance j here, the same hole might cut into the next range too.
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if start < other.ranges[k].start {
+                    ranges.push(start..other.ranges[k].start);
+                }
+                start = max(start, other.ranges[k].end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        Self::normalize(iter.into_iter().collect())
+    }
+}
+
+impl<T: Integer> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::normalize(vec![range])
+    }
+}
+
+// Sends every value in `source` to the same spot relative to `destination`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece<T> {
+    pub source: Range<T>,
+    pub destination: T,
+}
+
+impl<T: Integer> Piece<T> {
+    pub fn apply(&self, value: T) -> T {
+        value - self.source.start + self.destination
+    }
+
+    pub fn destination_range(&self) -> Range<T> {
+        self.destination..self.apply(self.source.end)
+    }
+
+    fn restrict(&self, range: Range<T>) -> Piece<T> {
+        Piece {
+            destination: self.apply(range.start),
+            source: range,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntervalError<T> {
+    Overlap(Range<T>, Range<T>),
+    // Some part of the output is reached from more than one input.
+    NotInvertible(Range<T>),
+}
+
+// Everything outside of the pieces maps to itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap<T> {
+    pieces: Vec<Piece<T>>,
+}
+
+impl<T: Integer> Default for PiecewiseMap<T> {
+    fn default() -> Self {
+        PiecewiseMap { pieces: vec![] }
+    }
+}
+
+impl<T: Integer> PiecewiseMap<T> {
+    pub fn new(mut pieces: Vec<Piece<T>>) -> Result<Self, IntervalError<T>> {
+        pieces.retain(|x| !x.source.is_empty());
+        pieces.sort_by_key(|x| x.source.start);
+        Self::validate(&pieces)?;
+        Ok(PiecewiseMap { pieces })
+    }
+
+    // Expects the pieces sorted by source start.
+    fn validate(pieces: &[Piece<T>]) -> Result<(), IntervalError<T>> {
+        for pair in pieces.windows(2) {
+            if pair[1].source.start < pair[0].source.end {
+                return Err(IntervalError::Overlap(
+                    pair[0].source.clone(),
+                    pair[1].source.clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn pieces(&self) -> &[Piece<T>] {
+        &self.pieces
+    }
+
+    pub fn sources(&self) -> IntervalSet<T> {
+        self.pieces.iter().map(|x| x.source.clone()).collect()
+    }
+
+    pub fn apply(&self, value: T) -> T {
+        let index = self.pieces.partition_point(|x| x.source.end <= value);
+        match self.pieces.get(index) {
+            Some(piece) if piece.source.contains(&value) => piece.apply(value),
+            _ => value,
+        }
+    }
+
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = vec![];
+        for piece in &self.pieces {
+            for range in set.ranges() {
+                if let Some(range) = intersect(&piece.source, range) {
+                    ranges.push(piece.restrict(range).destination_range());
+                }
+            }
+        }
+        ranges.extend(set.difference(&self.sources()).ranges().iter().cloned());
+        ranges.into_iter().collect()
+    }
+
+    // Every value that ends up somewhere inside `set`.
+    pub fn preimage(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = vec![];
+        for piece in &self.pieces {
+            for range in set.ranges() {
+                if let Some(range) = intersect(&piece.destination_range(), range) {
+                    let start = range.start - piece.destination + piece.source.start;
+                    ranges.push(start..start + (range.end - range.start));
+                }
+            }
+        }
+        ranges.extend(set.difference(&self.sources()).ranges().iter().cloned());
+        ranges.into_iter().collect()
+    }
+
+    // First `self`, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut pieces = vec![];
+        for piece in &self.pieces {
+            let image = piece.destination_range();
+            let mut rest = IntervalSet::from(image.clone());
+            for next_piece in &next.pieces {
+                if let Some(range) = intersect(&image, &next_piece.source) {
+                    let start = range.start - piece.destination + piece.source.start;
+                    pieces.push(Piece {
+                        source: start..start + (range.end - range.start),
+                        destination: next_piece.apply(range.start),
+                    });
+                    rest = rest.difference(&IntervalSet::from(range));
+                }
+            }
+            for range in rest.ranges() {
+                let start = range.start - piece.destination + piece.source.start;
+                pieces.push(Piece {
+                    source: start..start + (range.end - range.start),
+                    destination: range.start,
+                });
+            }
+        }
+        // Stuff that `self` leaves alone only goes through `next`.
+        let untouched = next.sources().difference(&self.sources());
+        for next_piece in &next.pieces {
+            for range in untouched.ranges() {
+                if let Some(range) = intersect(&next_piece.source, range) {
+                    pieces.push(next_piece.restrict(range));
+                }
+            }
+        }
+        pieces.retain(|x| x.source.start != x.destination);
+
+        Self::new(pieces).expect("composing two valid maps can't overlap")
+    }
+
+    pub fn invert(&self) -> Result<Self, IntervalError<T>> {
+        let mut inverted: Vec<_> = self
+            .pieces
+            .iter()
+            .map(|x| Piece {
+                source: x.destination_range(),
+                destination: x.source.start,
+            })
+            .collect();
+        inverted.sort_by_key(|x| x.source.start);
+        if let Err(IntervalError::Overlap(a, b)) = Self::validate(&inverted) {
+            return Err(IntervalError::NotInvertible(
+                max(a.start, b.start)..min(a.end, b.end),
+            ));
+        }
+
+        // Whatever lands outside of the pieces also gets hit by the identity part.
+        let images: IntervalSet<T> = inverted.iter().map(|x| x.source.clone()).collect();
+        if let Some(range) = images.difference(&self.sources()).ranges().first() {
+            return Err(IntervalError::NotInvertible(range.clone()));
+        }
+
+        Ok(PiecewiseMap { pieces: inverted })
+    }
+}
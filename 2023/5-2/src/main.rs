@@ -1,270 +1,304 @@
-#[warn(missing_debug_implementations)]
+#![warn(missing_debug_implementations)]
 use std::fs;
-use std::ops::{Range, RangeInclusive};
+use std::ops::Range;
 use std::str::FromStr;
 
-fn range_by_size(start: i64, size: i64) -> Range<i64> {
-    start..(start + size)
-}
+mod interval;
+mod parse;
+use interval::*;
+use parse::*;
 
-fn inclusive_range_by_size(start: i64, size: i64) -> RangeInclusive<i64> {
-    start..=(start + size)
+fn parse_piece(text: Text) -> Result<Piece<i64>, ParseError> {
+    let [destination, source, size]: [i64; 3] = text
+        .list(" ")?
+        .try_into()
+        .map_err(|_| text.error("three numbers"))?;
+    Ok(Piece {
+        source: source..(source + size),
+        destination,
+    })
 }
 
-fn move_range(range: &Range<i64>, offset: i64) -> Range<i64> {
-    range.start + offset..range.end + offset
+#[derive(Debug, Clone, Copy, Default)]
+struct Options {
+    // Overlaps go to whichever line came first instead of being an error.
+    lenient: bool,
+    list_gaps: bool,
 }
 
-#[derive(Debug)]
-struct ParseError {}
-
-impl From<std::num::ParseIntError> for ParseError {
-    fn from(_: std::num::ParseIntError) -> Self {
-        ParseError {}
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Overlap {
+    first_line: usize,
+    first: Range<i64>,
+    second_line: usize,
+    second: Range<i64>,
 }
 
-enum RangePosition {
-    NotOverlapping,
-    Inner,
-    Overlapping,
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct RangeMap {
-    range: Range<i64>,
-    offset: i64,
+#[derive(Debug)]
+enum AlmanacError {
+    Parse(ParseError),
+    Overlap { map: String, overlap: Overlap },
+    EmptyRange { map: String, line: usize },
 }
 
-impl RangeMap {
-    fn split(&self, splitter: i64) -> Option<(RangeMap, RangeMap)> {
-        if splitter != self.range.start && self.range.contains(&splitter) {
-            Some((
-                RangeMap {
-                    range: self.range.start..splitter,
-                    offset: self.offset,
-                },
-                RangeMap {
-                    range: splitter..self.range.end,
-                    offset: self.offset,
-                },
-            ))
-        } else {
-            None
-        }
-    }
-
-    fn split_range(&self, range: &Range<i64>) -> RangeMapped {
-        use RangePosition::*;
-        let split_range = &self.range;
-        let end_overlaps = match () {
-            _ if split_range.end <= range.start => NotOverlapping,
-            _ if split_range.end >= range.end => Overlapping,
-            _ => Inner,
-        };
-
-        let start_overlaps = match () {
-            _ if split_range.start >= range.end => NotOverlapping,
-            _ if split_range.start <= range.start => Overlapping,
-            _ => Inner,
-        };
-
-        match (start_overlaps, end_overlaps) {
-            (Inner, Inner) => RangeMapped::Inner(
-                range.start..self.range.start,
-                self.range.start..self.range.end,
-                self.range.end..range.end,
+impl std::fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlmanacError::Parse(e) => write!(f, "{}", e),
+            AlmanacError::Overlap { map, overlap } => write!(
+                f,
+                "{}: line {} ({:?}) overlaps line {} ({:?})",
+                map, overlap.first_line, overlap.first, overlap.second_line, overlap.second
             ),
-            (_, Inner) => RangeMapped::Left(range.start..self.range.end, self.range.end..range.end),
-            (Inner, _) => {
-                RangeMapped::Right(range.start..self.range.start, self.range.start..range.end)
-            }
-            (Overlapping, Overlapping) if self.range.contains(&range.start) => {
-                RangeMapped::Full(range.start..range.end)
+            AlmanacError::EmptyRange { map, line } => {
+                write!(f, "{}: line {} has a zero length range", map, line)
             }
-            _ => RangeMapped::None(range.start..range.end),
         }
     }
+}
 
-    fn map_range(&self, range: &Range<i64>) -> RangeMapped {
-        match self.split_range(range) {
-            RangeMapped::None(n) => RangeMapped::None(n),
-            RangeMapped::Inner(l, m, r) => RangeMapped::Inner(l, move_range(&m, self.offset), r),
-            RangeMapped::Left(m, r) => RangeMapped::Left(move_range(&m, self.offset), r),
-            RangeMapped::Right(l, m) => RangeMapped::Right(l, move_range(&m, self.offset)),
-            RangeMapped::Full(m) => RangeMapped::Full(move_range(&m, self.offset)),
-        }
+impl From<ParseError> for AlmanacError {
+    fn from(value: ParseError) -> Self {
+        AlmanacError::Parse(value)
     }
 }
 
-impl FromStr for RangeMap {
-    type Err = ParseError;
+// Whatever got let through in lenient mode, and the gaps if asked for.
+#[derive(Debug, Default)]
+struct Diagnostics {
+    overlaps: Vec<Overlap>,
+    empty_lines: Vec<usize>,
+    // Stretches between the ranges where numbers map to themselves.
+    gaps: Vec<Range<i64>>,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut splitted = s.split_whitespace();
-
-        let destination: i64 = splitted.next().ok_or(ParseError {})?.parse()?;
-        let source: i64 = splitted.next().ok_or(ParseError {})?.parse()?;
-        let size: i64 = splitted.next().ok_or(ParseError {})?.parse()?;
-        Ok(RangeMap {
-            range: range_by_size(source, size),
-            offset: destination - source,
-        })
+impl Diagnostics {
+    fn is_empty(&self) -> bool {
+        self.overlaps.is_empty() && self.empty_lines.is_empty() && self.gaps.is_empty()
     }
 }
 
 #[derive(Debug)]
 struct Map {
-    range_maps: Vec<RangeMap>,
+    name: String,
+    map: PiecewiseMap<i64>,
+    diagnostics: Diagnostics,
 }
 
 impl Map {
-    fn map_range(&self, range: Range<i64>) -> Vec<Range<i64>> {
-        let mut unmapped_ranges = vec![range];
-        let mut mapped_ranges = vec![];
-        for i in &self.range_maps {
-            let mut new_unmapped_ranges = vec![];
-            for j in unmapped_ranges {
-                match i.map_range(&j) {
-                    RangeMapped::None(n) => new_unmapped_ranges.push(n),
-                    RangeMapped::Inner(l, m, r) => {
-                        new_unmapped_ranges.push(l);
-                        new_unmapped_ranges.push(r);
-                        mapped_ranges.push(m);
-                    }
-                    RangeMapped::Left(m, r) => {
-                        new_unmapped_ranges.push(r);
-                        mapped_ranges.push(m);
-                    }
-                    RangeMapped::Right(l, m) => {
-                        new_unmapped_ranges.push(l);
-                        mapped_ranges.push(m);
-                    }
-                    RangeMapped::Full(m) => {
-                        mapped_ranges.push(m);
+    fn parse(text: Text, options: Options) -> Result<Self, AlmanacError> {
+        let (name, body) = text.key_value()?;
+        let name = name.as_str().trim_end_matches(" map").to_string();
+        let lines: Vec<(usize, Piece<i64>)> = body
+            .lines()
+            .map(|x| Ok((x.line(), parse_piece(x)?)))
+            .collect::<Result<_, ParseError>>()?;
+
+        let mut diagnostics = Diagnostics::default();
+        for (line, piece) in &lines {
+            if piece.source.is_empty() {
+                if !options.lenient {
+                    return Err(AlmanacError::EmptyRange {
+                        map: name,
+                        line: *line,
+                    });
+                }
+                diagnostics.empty_lines.push(*line);
+            }
+        }
+        for (i, (first_line, first)) in lines.iter().enumerate() {
+            for (second_line, second) in &lines[i + 1..] {
+                let intersection = IntervalSet::from(first.source.clone())
+                    .intersection(&IntervalSet::from(second.source.clone()));
+                if !intersection.is_empty() {
+                    let overlap = Overlap {
+                        first_line: *first_line,
+                        first: first.source.clone(),
+                        second_line: *second_line,
+                        second: second.source.clone(),
+                    };
+                    if !options.lenient {
+                        return Err(AlmanacError::Overlap { map: name, overlap });
                     }
+                    diagnostics.overlaps.push(overlap);
                 }
             }
-            unmapped_ranges = new_unmapped_ranges;
         }
-        mapped_ranges.extend(unmapped_ranges.into_iter());
-        mapped_ranges
-    }
-}
 
-impl FromStr for Map {
-    type Err = ParseError;
+        // First match wins: each line only keeps what the lines above it haven't claimed yet.
+        let mut pieces = vec![];
+        let mut claimed = IntervalSet::new();
+        for (_, piece) in &lines {
+            let unclaimed = IntervalSet::from(piece.source.clone()).difference(&claimed);
+            for range in unclaimed.ranges() {
+                pieces.push(Piece {
+                    destination: piece.apply(range.start),
+                    source: range.clone(),
+                });
+            }
+            claimed.insert(piece.source.clone());
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut map = Map { range_maps: vec![] };
-        for l in s.split('\n').skip(1).filter(|x| x != &"") {
-            map.range_maps.push(l.parse::<RangeMap>()?);
+        if options.list_gaps {
+            if let (Some(first), Some(last)) = (claimed.ranges().first(), claimed.ranges().last()) {
+                diagnostics.gaps = IntervalSet::from(first.start..last.end)
+                    .difference(&claimed)
+                    .ranges()
+                    .to_vec();
+            }
         }
-        Ok(map)
-    }
-}
 
-#[derive(Debug)]
-enum RangeMapped {
-    None(Range<i64>),
-    Inner(Range<i64>, Range<i64>, Range<i64>),
-    Left(Range<i64>, Range<i64>),
-    Right(Range<i64>, Range<i64>),
-    Full(Range<i64>),
+        Ok(Map {
+            name,
+            map: PiecewiseMap::new(pieces).expect("overlaps are sorted out above"),
+            diagnostics,
+        })
+    }
 }
 
 #[derive(Debug)]
 struct ParsedInput {
-    seeds: Vec<Range<i64>>,
+    // First gold star reads the seed line as plain seeds...
+    seed_numbers: Vec<i64>,
+    // ...second gold star reads it as (start, length) pairs.
+    seeds: IntervalSet<i64>,
     maps: Vec<Map>,
+    // Every map one after another, seed to location in one go.
+    composed: PiecewiseMap<i64>,
 }
 
 impl ParsedInput {
-    fn map_range(&self, range: Range<i64>) -> Vec<Range<i64>> {
-        let mut ranges = vec![range];
-        dbg!(&ranges);
-        for i in &self.maps {
-            let mut new_ranges = vec![];
-            for j in ranges {
-                new_ranges.append(&mut i.map_range(j));
-            }
-            ranges = new_ranges;
-            dbg!(&ranges);
-        }
-        ranges
+    fn location(&self, seed: i64) -> i64 {
+        self.composed.apply(seed)
     }
 
-    fn solve(&self) -> Option<i64> {
-        Some(
-            self.seeds
+    fn locations(&self, seeds: &IntervalSet<i64>) -> IntervalSet<i64> {
+        self.composed.apply_set(seeds)
+    }
+
+    // Any seed at all, not only the ones we've got.
+    fn seeds_for(&self, locations: &IntervalSet<i64>) -> IntervalSet<i64> {
+        self.composed.preimage(locations)
+    }
+
+    // Only when no two seeds share a location.
+    fn location_to_seed(&self) -> Result<PiecewiseMap<i64>, IntervalError<i64>> {
+        self.composed.invert()
+    }
+
+    fn lowest_location(&self) -> Option<i64> {
+        self.locations(&self.seeds).min()
+    }
+
+    fn lowest_location_of_seed_numbers(&self) -> Option<i64> {
+        self.seed_numbers.iter().map(|x| self.location(*x)).min()
+    }
+
+    // Which of our seeds end up at `location`.
+    fn seeds_at(&self, location: i64) -> IntervalSet<i64> {
+        self.seeds_for(&IntervalSet::from(location..location + 1))
+            .intersection(&self.seeds)
+    }
+
+    fn print_table(&self) {
+        println!(
+            "{}",
+            self.maps
                 .iter()
-                .map(|seed| {
-                    self.map_range(seed.clone())
-                        .iter()
-                        .filter(|x| x.start < x.end)
-                        .map(|x| x.start)
-                        .min()
-                        .unwrap()
-                })
-                .min()
-                .unwrap(),
-        )
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" then ")
+        );
+        for piece in self.composed.pieces() {
+            println!(
+                "{:>12}..{:<12} -> {}..{}",
+                piece.source.start,
+                piece.source.end,
+                piece.destination_range().start,
+                piece.destination_range().end,
+            );
+        }
+        println!("everything else stays put");
     }
 }
 
 impl FromStr for ParsedInput {
-    type Err = ParseError;
+    type Err = AlmanacError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut splitted = s.split("\n\n");
-        let mut seeds: Vec<Range<i64>> = vec![];
-        for i in splitted
-            .next()
-            .ok_or(ParseError {})?
-            .split(": ")
-            .skip(1)
+        Self::parse(s, Options::default())
+    }
+}
+
+impl ParsedInput {
+    fn parse(s: &str, options: Options) -> Result<Self, AlmanacError> {
+        let text = Text::new(s);
+        let mut sections = text.sections();
+        let (_, seeds_text) = sections
             .next()
-            .ok_or(ParseError {})?
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .chunks(2)
-        {
-            let start: i64 = i[0].parse()?;
-            let size: i64 = i[1].parse()?;
-            seeds.push(start..(start + size));
-        }
-        let mut maps = vec![];
-        for s in splitted {
-            maps.push(s.parse::<Map>()?);
+            .ok_or_else(|| text.error("seeds"))?
+            .key_value()?;
+
+        let seed_numbers: Vec<i64> = seeds_text.list(" ")?;
+        let mut seeds = IntervalSet::new();
+        for i in seed_numbers.chunks(2) {
+            match i {
+                [start, size] => seeds.insert(*start..(start + size)),
+                _ => return Err(seeds_text.error("pairs of numbers").into()),
+            }
         }
-        Ok(ParsedInput { seeds, maps })
+        let maps: Vec<Map> = sections
+            .map(|x| Map::parse(x, options))
+            .collect::<Result<_, _>>()?;
+        let composed = maps
+            .iter()
+            .fold(PiecewiseMap::default(), |composed, x| composed.then(&x.map));
+
+        Ok(ParsedInput {
+            seed_numbers,
+            seeds,
+            maps,
+            composed,
+        })
     }
 }
 
 fn main() {
-    let input = fs::read_to_string("input").unwrap();
+    let args: Vec<String> = std::env::args().collect();
+    let options = Options {
+        lenient: args.iter().any(|x| x == "--lenient"),
+        list_gaps: args.iter().any(|x| x == "--gaps"),
+    };
 
-    dbg!(RangeMap {
-        range: 0..10,
-        offset: 100,
-    }
-    .map_range(&(10..20)));
+    let input = fs::read_to_string("input").unwrap();
+    let parsed_input = match ParsedInput::parse(&input, options) {
+        Ok(parsed_input) => parsed_input,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
 
-    let parsed_input: ParsedInput = input.parse().unwrap();
     for map in &parsed_input.maps {
-        for range_map in &map.range_maps {
-            for range_map2 in &map.range_maps {
-                if range_map != range_map2 {
-                    println!("{:?} {:?}", range_map.range, range_map2.range);
-                    match range_map.split_range(&range_map2.range) {
-                        RangeMapped::None(_) => {}
-                        _ => panic!(),
-                    }
-                }
-            }
+        if !map.diagnostics.is_empty() {
+            println!("{}: {:?}", map.name, map.diagnostics);
         }
     }
-    dbg!(&parsed_input);
+    if args.iter().any(|x| x == "--table") {
+        parsed_input.print_table();
+    }
 
-    dbg!(parsed_input.solve());
+    dbg!(parsed_input.lowest_location_of_seed_numbers());
+    let lowest = parsed_input.lowest_location().unwrap();
+    dbg!(lowest);
+    dbg!(parsed_input.seeds_at(lowest));
+
+    match parsed_input.location_to_seed() {
+        Ok(inverse) => {
+            dbg!(inverse.apply(lowest));
+        }
+        Err(e) => {
+            dbg!(e);
+        }
+    }
 }
@@ -1,20 +1,13 @@
 #![warn(missing_debug_implementations)]
 
-use enum_map::{enum_map, Enum, EnumMap};
-use std::{fs, num::ParseIntError, str::FromStr};
+use enum_map::{Enum, EnumMap};
+use std::{cmp::Ordering, fs, str::FromStr};
 
-#[derive(Debug)]
-struct ParseError {}
+mod parse;
+use parse::*;
 
-impl From<ParseIntError> for ParseError {
-    fn from(value: ParseIntError) -> Self {
-        ParseError {}
-    }
-}
-
-#[derive(Debug, Copy, Clone, Enum, PartialEq, Eq, Ord)]
+#[derive(Debug, Copy, Clone, Enum, PartialEq, Eq)]
 enum Card {
-    J,
     Two,
     Three,
     Four,
@@ -24,42 +17,54 @@ enum Card {
     Eight,
     Nine,
     T,
+    J,
     Q,
     K,
     A,
 }
 
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (*self as usize).partial_cmp(&(*other as usize))
+impl Card {
+    fn from_char(c: char) -> Option<Self> {
+        use Card::*;
+        match c {
+            'A' => Some(A),
+            'K' => Some(K),
+            'Q' => Some(Q),
+            'J' => Some(J),
+            'T' => Some(T),
+            '9' => Some(Nine),
+            '8' => Some(Eight),
+            '7' => Some(Seven),
+            '6' => Some(Six),
+            '5' => Some(Five),
+            '4' => Some(Four),
+            '3' => Some(Three),
+            '2' => Some(Two),
+            _ => None,
+        }
     }
-}
-
-impl TryFrom<char> for Card {
-    type Error = ParseError;
 
-    fn try_from(s: char) -> Result<Self, Self::Error> {
+    fn to_char(self) -> char {
         use Card::*;
-        match s {
-            'A' => Ok(A),
-            'K' => Ok(K),
-            'Q' => Ok(Q),
-            'J' => Ok(J),
-            'T' => Ok(T),
-            '9' => Ok(Nine),
-            '8' => Ok(Eight),
-            '7' => Ok(Seven),
-            '6' => Ok(Six),
-            '5' => Ok(Five),
-            '4' => Ok(Four),
-            '3' => Ok(Three),
-            '2' => Ok(Two),
-            _ => Err(ParseError {}),
+        match self {
+            A => 'A',
+            K => 'K',
+            Q => 'Q',
+            J => 'J',
+            T => 'T',
+            Nine => '9',
+            Eight => '8',
+            Seven => '7',
+            Six => '6',
+            Five => '5',
+            Four => '4',
+            Three => '3',
+            Two => '2',
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum RowType {
     HighCard,
     OnePair,
@@ -70,106 +75,213 @@ enum RowType {
     FiveOfAKind,
 }
 
-impl PartialOrd for RowType {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (*self as usize).partial_cmp(&(*other as usize))
-    }
+#[derive(Debug, Clone)]
+struct Rules {
+    // Cards that turn into whatever makes the best hand.
+    wild: EnumMap<Card, bool>,
+    // Where each card sits when breaking ties, higher is stronger.
+    strength: EnumMap<Card, usize>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord)]
-struct Row {
-    cards: [Card; 5],
-}
+impl Rules {
+    // `order` goes weakest to strongest and has to have every card exactly once.
+    fn new(order: &str, wild: &str) -> Result<Self, ParseError> {
+        let order_text = Text::new(order);
+        let mut strength = EnumMap::default();
+        let mut seen: EnumMap<Card, bool> = EnumMap::default();
+        for (i, card) in order_text
+            .chars_map("card", Card::from_char)?
+            .into_iter()
+            .enumerate()
+        {
+            if seen[card] {
+                return Err(order_text.error("every card once"));
+            }
+            seen[card] = true;
+            strength[card] = i;
+        }
+        if seen.values().any(|x| !x) {
+            return Err(order_text.error("every card once"));
+        }
 
-impl PartialOrd for Row {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        let type_cmp = self.row_type().cmp(&other.row_type());
-        Some(match type_cmp {
-            std::cmp::Ordering::Equal => self.cards.cmp(&other.cards),
-            a => a,
+        let mut wild_cards = EnumMap::default();
+        for card in Text::new(wild).chars_map("card", Card::from_char)? {
+            wild_cards[card] = true;
+        }
+
+        Ok(Rules {
+            wild: wild_cards,
+            strength,
         })
     }
-}
 
-impl Row {
-    fn row_type(&self) -> RowType {
-        let counts: EnumMap<Card, u32> = enum_map!(card => self.cards.iter().fold(0, |init, x| init + match *x == card{ true => 1, false => 0 }));
-        let joker = counts[Card::J];
-        let mut max_row_type = RowType::HighCard;
-        for (card, _) in &counts {
-            let mut counts = counts.clone();
-            counts[card] += joker;
-
-            let mut five = 0;
-            let mut four = 0;
-            let mut three = 0;
-            let mut two = 0;
-            let mut one = 0;
-            for (card, count) in counts {
-                if card == Card::J {
-                    continue;
-                }
-                match count {
-                    5 => five += 1,
-                    4 => four += 1,
-                    3 => three += 1,
-                    2 => two += 1,
-                    1 => one += 1,
-                    _ => (),
-                }
+    // First gold star
+    fn plain() -> Self {
+        Self::new("23456789TJQKA", "").unwrap()
+    }
+
+    // Second gold star
+    fn jokers() -> Self {
+        Self::new("J23456789TQKA", "J").unwrap()
+    }
+
+    // Also says which card the wild cards pretended to be, if there were any.
+    fn classify(&self, row: &Row) -> (RowType, Option<Card>) {
+        let mut counts: EnumMap<Card, usize> = EnumMap::default();
+        let mut wild = 0;
+        for card in row.cards {
+            match self.wild[card] {
+                true => wild += 1,
+                false => counts[card] += 1,
             }
-            max_row_type = max_row_type.max(if five >= 1 {
-                RowType::FiveOfAKind
-            } else if four >= 1 {
-                RowType::FourOfAKind
-            } else if three >= 1 && two >= 1 {
-                RowType::FullHouse
-            } else if three >= 1 {
-                RowType::ThreeOfAKind
-            } else if two >= 2 {
-                RowType::TwoPair
-            } else if two >= 1 {
-                RowType::OnePair
-            } else {
-                RowType::HighCard
+        }
+
+        // Wild cards always do best joining the biggest group, the stronger card if it's a tie.
+        let target = counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .max_by_key(|(card, count)| (**count, self.strength[*card]))
+            .map(|(card, _)| card)
+            .unwrap_or_else(|| {
+                // Nothing but wild cards, so they might as well all be the strongest card there is.
+                counts
+                    .iter()
+                    .max_by_key(|(card, _)| self.strength[*card])
+                    .unwrap()
+                    .0
             });
+        counts[target] += wild;
+
+        let mut sorted: Vec<usize> = counts.values().copied().filter(|x| *x > 0).collect();
+        sorted.sort_by(|a, b| b.cmp(a));
+
+        let row_type = match sorted[..] {
+            [5, ..] => RowType::FiveOfAKind,
+            [4, ..] => RowType::FourOfAKind,
+            [3, 2, ..] => RowType::FullHouse,
+            [3, ..] => RowType::ThreeOfAKind,
+            [2, 2, ..] => RowType::TwoPair,
+            [2, ..] => RowType::OnePair,
+            _ => RowType::HighCard,
+        };
+        (row_type, (wild > 0).then_some(target))
+    }
+
+    fn row_type(&self, row: &Row) -> RowType {
+        self.classify(row).0
+    }
+
+    // Why `a` beats `b`, as far as these rules go.
+    fn reason(&self, a: &Row, b: &Row) -> Reason {
+        let (a_type, b_type) = (self.row_type(a), self.row_type(b));
+        if a_type != b_type {
+            return Reason::RowType(b_type);
+        }
+        match (0..5).find(|i| a.cards[*i] != b.cards[*i]) {
+            Some(position) => Reason::Card {
+                position,
+                ours: a.cards[position],
+                theirs: b.cards[position],
+            },
+            None => Reason::Identical,
+        }
+    }
+
+    fn cmp(&self, a: &Row, b: &Row) -> Ordering {
+        self.row_type(a).cmp(&self.row_type(b)).then_with(|| {
+            a.cards
+                .iter()
+                .map(|x| self.strength[*x])
+                .cmp(b.cards.iter().map(|x| self.strength[*x]))
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Row {
+    cards: [Card; 5],
+}
+
+impl std::fmt::Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for card in self.cards {
+            write!(f, "{}", card.to_char())?;
         }
-        max_row_type
+        Ok(())
     }
 }
 
-impl FromStr for Row {
-    type Err = ParseError;
+// How a hand got past the one ranked right below it.
+#[derive(Debug, Clone, Copy)]
+enum Reason {
+    // Nothing below it.
+    Lowest,
+    // Better type than the one below, which was this.
+    RowType(RowType),
+    // Same type, first differing card decides.
+    Card {
+        position: usize,
+        ours: Card,
+        theirs: Card,
+    },
+    // Same cards, so it's down to the sort.
+    Identical,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut cards = [Card::A; 5];
-        for (i, c) in s.chars().enumerate() {
-            cards[i] = c.try_into()?;
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Lowest => write!(f, "lowest"),
+            Reason::RowType(row_type) => write!(f, "beats {:?}", row_type),
+            Reason::Card {
+                position,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "card {}: {} over {}",
+                position + 1,
+                ours.to_char(),
+                theirs.to_char()
+            ),
+            Reason::Identical => write!(f, "identical"),
         }
-        Ok(Row { cards })
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord)]
-struct Item {
+#[derive(Debug)]
+struct RankedHand {
     row: Row,
     bid: u32,
+    row_type: RowType,
+    wild_as: Option<Card>,
+    rank: u32,
+    winnings: u32,
+    reason: Reason,
 }
 
-impl PartialOrd for Item {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.row.partial_cmp(&other.row)
+impl FromText for Row {
+    fn from_text(text: Text) -> Result<Self, ParseError> {
+        let cards = text.chars_map("card", Card::from_char)?;
+        Ok(Row {
+            cards: cards.try_into().map_err(|_| text.error("five cards"))?,
+        })
     }
 }
 
-impl FromStr for Item {
-    type Err = ParseError;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Item {
+    row: Row,
+    bid: u32,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut splitted = s.split_whitespace();
-        let row = splitted.next().ok_or(ParseError {})?.parse()?;
-        let bid = splitted.next().ok_or(ParseError {})?.parse()?;
-        Ok(Item { row, bid })
+impl FromText for Item {
+    fn from_text(text: Text) -> Result<Self, ParseError> {
+        let (row, bid) = text.trim().split_once(" ")?;
+        Ok(Item {
+            row: row.read()?,
+            bid: bid.parse()?,
+        })
     }
 }
 
@@ -179,31 +291,83 @@ struct Input {
 }
 
 impl Input {
-    fn solve(&self) -> u32 {
+    // Weakest first.
+    fn rank(&self, rules: &Rules) -> Vec<RankedHand> {
         let mut items = self.items.clone();
-        items.sort();
-        let mut summation = 0;
-        for (rank, score) in items
-            .iter()
-            .enumerate()
-            .map(|(index, x)| ((index + 1) as u32, x.bid))
-        {
-            summation += rank * score;
+        items.sort_by(|a, b| rules.cmp(&a.row, &b.row));
+
+        let mut ranked: Vec<RankedHand> = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let (row_type, wild_as) = rules.classify(&item.row);
+            let rank = (index + 1) as u32;
+            ranked.push(RankedHand {
+                row: item.row,
+                bid: item.bid,
+                row_type,
+                wild_as,
+                rank,
+                winnings: rank * item.bid,
+                reason: match index {
+                    0 => Reason::Lowest,
+                    _ => rules.reason(&item.row, &items[index - 1].row),
+                },
+            });
         }
-        summation
+        ranked
+    }
+
+    fn solve(&self, rules: &Rules) -> u32 {
+        self.rank(rules).iter().map(|x| x.winnings).sum()
     }
 }
 
+fn print_table(ranked: &[RankedHand]) {
+    println!(
+        "{:>5} {:5} {:>5} {:12} {:4} {:>9}  why",
+        "rank", "hand", "bid", "type", "wild", "winnings"
+    );
+    for hand in ranked {
+        println!(
+            "{:>5} {} {:>5} {:12} {:4} {:>9}  {}",
+            hand.rank,
+            hand.row,
+            hand.bid,
+            format!("{:?}", hand.row_type),
+            hand.wild_as.map_or('-', |x| x.to_char()),
+            hand.winnings,
+            hand.reason
+        );
+    }
+}
+
+fn print_json(ranked: &[RankedHand]) {
+    println!("[");
+    for (i, hand) in ranked.iter().enumerate() {
+        println!(
+            "  {{\"rank\": {}, \"hand\": \"{}\", \"bid\": {}, \"type\": \"{:?}\", \"wild_as\": {}, \"winnings\": {}, \"reason\": \"{}\"}}{}",
+            hand.rank,
+            hand.row,
+            hand.bid,
+            hand.row_type,
+            hand.wild_as
+                .map_or("null".to_string(), |x| format!("\"{}\"", x.to_char())),
+            hand.winnings,
+            hand.reason,
+            if i + 1 < ranked.len() { "," } else { "" }
+        );
+    }
+    println!("]");
+}
+
 impl FromStr for Input {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Input {
-            items: s
-                .split('\n')
-                .filter(|x| !x.is_empty())
-                .filter_map(|x| x.parse::<Item>().ok())
-                .collect(),
+            items: Text::new(s)
+                .lines()
+                .map(|x| x.read())
+                .collect::<Result<_, _>>()?,
         })
     }
 }
@@ -214,5 +378,19 @@ fn main() {
         .parse::<Input>()
         .unwrap();
 
-    dbg!(input.solve());
+    // `--table` or `--json` shows how the hands got ranked, `--plain` for the first star's rules.
+    let args: Vec<String> = std::env::args().collect();
+    let rules = match args.iter().any(|x| x == "--plain") {
+        true => Rules::plain(),
+        false => Rules::jokers(),
+    };
+    if args.iter().any(|x| x == "--table") {
+        print_table(&input.rank(&rules));
+    }
+    if args.iter().any(|x| x == "--json") {
+        print_json(&input.rank(&rules));
+    }
+
+    dbg!(input.solve(&Rules::plain()));
+    dbg!(input.solve(&Rules::jokers()));
 }
@@ -1,37 +1,19 @@
 #![warn(missing_debug_implementations)]
 
 use enum_map::{enum_map, Enum, EnumMap};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
+use std::io::{self, Write};
+use std::process;
 use std::str::FromStr;
 
-#[derive(Debug)]
-struct ParseError {}
-
-fn char_to_index(c: char) -> Option<usize> {
-    let index = (c as usize).wrapping_sub('A' as usize);
-    if (0..26).contains(&index) {
-        Some(index)
-    } else {
-        None
-    }
-}
-
-fn node_string_to_index(node_string: &str) -> Option<usize> {
-    if node_string.len() != 3 {
-        return None;
-    }
+mod cycle;
+mod math;
+mod parse;
+use math::{Congruence, CrtError};
+use parse::*;
 
-    let mut index = 0;
-
-    for c in node_string.chars() {
-        index *= 26;
-        index += char_to_index(c)?;
-    }
-
-    Some(index)
-}
-
-#[derive(Debug, Enum, Clone, Copy)]
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, Hash)]
 enum Turn {
     Left,
     Right,
@@ -44,120 +26,415 @@ enum NodeType {
     None,
 }
 
-impl TryFrom<char> for Turn {
-    type Error = ParseError;
+impl Turn {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'L' => Some(Turn::Left),
+            'R' => Some(Turn::Right),
+            _ => None,
+        }
+    }
 
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            'L' => Ok(Turn::Left),
-            'R' => Ok(Turn::Right),
-            _ => Err(ParseError {}),
+    fn to_char(self) -> char {
+        match self {
+            Turn::Left => 'L',
+            Turn::Right => 'R',
         }
     }
 }
 
-#[derive(Debug)]
-struct Node {
-    node_index: usize,
-    destinations: EnumMap<Turn, usize>,
-    node_type: NodeType,
+#[derive(Debug, Clone)]
+struct SuffixRule {
+    starting: String,
+    ending: String,
 }
 
-impl FromStr for Node {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (node_string, node_destination_string) = s.split_once(" = ").ok_or(ParseError {})?;
-        let (left_node_string, right_node_string) = node_destination_string[1..9]
-            .split_once(", ")
-            .ok_or(ParseError {})?;
-
-        let (node_index, left_node_index, right_node_index) = (
-            node_string_to_index(node_string).ok_or(ParseError {})?,
-            node_string_to_index(left_node_string).ok_or(ParseError {})?,
-            node_string_to_index(right_node_string).ok_or(ParseError {})?,
-        );
-
-        Ok(Self {
-            node_index,
-            destinations: enum_map! {
-                Turn::Left => left_node_index,
-                Turn::Right => right_node_index,
-            },
-            node_type: if node_string.ends_with('A') {
-                NodeType::Starting
-            } else if node_string.ends_with('Z') {
-                NodeType::Ending
-            } else {
-                NodeType::None
-            },
-        })
+impl Default for SuffixRule {
+    fn default() -> Self {
+        SuffixRule {
+            starting: "A".to_string(),
+            ending: "Z".to_string(),
+        }
     }
 }
 
-impl Default for Node {
-    fn default() -> Self {
-        Self {
-            node_index: 0,
-            destinations: enum_map! { _ =>  0},
-            node_type: NodeType::None,
+impl SuffixRule {
+    fn node_type(&self, name: &str) -> NodeType {
+        if name.ends_with(&self.starting) {
+            NodeType::Starting
+        } else if name.ends_with(&self.ending) {
+            NodeType::Ending
+        } else {
+            NodeType::None
         }
     }
 }
 
+#[derive(Debug)]
+struct Node {
+    destinations: EnumMap<Turn, usize>,
+    node_type: NodeType,
+}
+
+fn node_name<'a>(text: Text<'a>) -> Result<Text<'a>, ParseError> {
+    let (name, rest) = text.trim().identifier()?;
+    if !rest.is_empty() {
+        return Err(rest.error("end of the node name"));
+    }
+    Ok(name)
+}
+
+// Node names get numbered in the order they're defined, `graph[i]` is the node called `names[i]`.
 #[derive(Debug)]
 struct Input {
     turns: Vec<Turn>,
-    graph: [Node; 26 * 26 * 26],
+    names: Vec<String>,
+    graph: Vec<Node>,
+    starting: Vec<usize>,
+    ending: Vec<usize>,
 }
 
 impl FromStr for Input {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (turn_string, graph_string) = s.trim().split_once("\n\n").unwrap();
+        Self::parse(s, &SuffixRule::default())
+    }
+}
 
-        let mut new = Input {
-            turns: vec![],
-            graph: core::array::from_fn(|_| Node::default()),
-        };
+impl Input {
+    fn parse(s: &str, rule: &SuffixRule) -> Result<Self, ParseError> {
+        let text = Text::new(s);
+        let mut sections = text.sections();
+        let turn_text = sections.next().ok_or_else(|| text.error("turns"))?;
+        let graph_text = sections.next().ok_or_else(|| text.error("nodes"))?;
 
-        for c in turn_string.chars() {
-            new.turns.push(TryInto::<Turn>::try_into(c)?);
+        let mut definitions = vec![];
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for l in graph_text.lines() {
+            let (name, destinations) = l.split_once("=")?;
+            let name = node_name(name)?;
+            let (left, right) = destinations.tuple()?;
+            if let Some(&first) = indices.get(name.as_str()) {
+                let (first_name, _, _): &(Text, Text, Text) = &definitions[first];
+                return Err(name.error(format!(
+                    "a new node, this one is already on line {}",
+                    first_name.line()
+                )));
+            }
+            indices.insert(name.as_str(), definitions.len());
+            definitions.push((name, node_name(left)?, node_name(right)?));
         }
 
-        for l in graph_string.split('\n') {
-            let node: Node = l.parse()?;
-            let index = node.node_index;
-            new.graph[index] = node;
+        let index = |t: Text| {
+            indices
+                .get(t.as_str())
+                .copied()
+                .ok_or_else(|| t.error("a node that's defined somewhere"))
+        };
+        let mut graph = vec![];
+        for (name, left, right) in &definitions {
+            graph.push(Node {
+                destinations: enum_map! {
+                    Turn::Left => index(*left)?,
+                    Turn::Right => index(*right)?,
+                },
+                node_type: rule.node_type(name.as_str()),
+            });
         }
 
-        Ok(new)
+        let of_type = |node_type| {
+            (0..graph.len())
+                .filter(|x| graph[*x].node_type == node_type)
+                .collect()
+        };
+        Ok(Input {
+            turns: turn_text.chars_map("L or R", Turn::from_char)?,
+            names: definitions
+                .iter()
+                .map(|(name, _, _)| name.as_str().to_string())
+                .collect(),
+            starting: of_type(NodeType::Starting),
+            ending: of_type(NodeType::Ending),
+            graph,
+        })
+    }
+}
+
+// One ghost, walked over (node, turn index) states. After `tail` steps it loops every `period`.
+#[derive(Debug)]
+struct GhostCycle {
+    start: usize,
+    tail: usize,
+    period: usize,
+    // Steps in 0..tail + period where the ghost stands on an ending node.
+    hits: Vec<usize>,
+}
+
+impl GhostCycle {
+    fn at_end(&self, step: usize) -> bool {
+        let step = if step < self.tail {
+            step
+        } else {
+            self.tail + (step - self.tail) % self.period
+        };
+        self.hits.binary_search(&step).is_ok()
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum Alignment {
+    At(u128),
+    Never,
+}
+
 impl Input {
-    fn solve(&self) -> u128 {
-        self.graph
+    fn step(&self, &(node, turn): &(usize, usize)) -> (usize, usize) {
+        (
+            self.graph[node].destinations[self.turns[turn]],
+            (turn + 1) % self.turns.len(),
+        )
+    }
+
+    fn ghost_cycle(&self, start: usize) -> GhostCycle {
+        let cycle::Cycle { tail, period } = cycle::find_cycle((start, 0), |x| self.step(x));
+        let mut hits = vec![];
+        let mut state = (start, 0);
+        for step in 0..tail + period {
+            if self.graph[state.0].node_type == NodeType::Ending {
+                hits.push(step);
+            }
+            state = self.step(&state);
+        }
+        GhostCycle {
+            start,
+            tail,
+            period,
+            hits,
+        }
+    }
+
+    fn ghost_cycles(&self) -> Vec<GhostCycle> {
+        self.starting.iter().map(|x| self.ghost_cycle(*x)).collect()
+    }
+
+    fn earliest_alignment(ghosts: &[GhostCycle]) -> Result<Alignment, CrtError> {
+        let settled = match ghosts.iter().map(|x| x.tail).max() {
+            Some(settled) => settled,
+            None => return Ok(Alignment::Never),
+        };
+
+        // Before everyone's on their loop, just go step by step.
+        for step in 0..settled {
+            if ghosts.iter().all(|x| x.at_end(step)) {
+                return Ok(Alignment::At(step as u128));
+            }
+        }
+
+        // After that every hit comes back once a period, so each pick of one hit per ghost
+        // is a system of congruences. Try them all.
+        let loop_hits: Vec<Vec<usize>> = ghosts
             .iter()
-            .filter(|x| x.node_type == NodeType::Starting)
-            .map(|n| {
-                let mut node = n;
-                let mut move_count: u128 = 0;
-                while node.node_type != NodeType::Ending {
-                    node = &self.graph
-                        [node.destinations[self.turns[(move_count as usize) % self.turns.len()]]];
-                    move_count += 1;
+            .map(|x| x.hits.iter().copied().filter(|h| *h >= x.tail).collect())
+            .collect();
+        let mut best: Option<i128> = None;
+        let mut picks = vec![0; ghosts.len()];
+        'outer: loop {
+            if loop_hits.iter().any(|x| x.is_empty()) {
+                break;
+            }
+            let congruences =
+                ghosts
+                    .iter()
+                    .zip(&picks)
+                    .enumerate()
+                    .map(|(i, (x, pick))| Congruence {
+                        remainder: loop_hits[i][*pick] as i128,
+                        modulus: x.period as i128,
+                    });
+            match math::crt(congruences) {
+                Ok(Congruence { remainder, modulus }) => {
+                    let settled = settled as i128;
+                    let step = if remainder >= settled {
+                        remainder
+                    } else {
+                        remainder + (settled - remainder + modulus - 1) / modulus * modulus
+                    };
+                    best = Some(best.map_or(step, |x| x.min(step)));
+                }
+                Err(CrtError::Inconsistent(..)) => (),
+                Err(e) => return Err(e),
+            }
+
+            // Next combination, like counting with mixed digits.
+            for i in 0..picks.len() {
+                picks[i] += 1;
+                if picks[i] < loop_hits[i].len() {
+                    continue 'outer;
                 }
-                dbg!(&move_count);
-                move_count
-            })
-            .fold(1, |init, x| num::integer::lcm(init, x))
+                picks[i] = 0;
+            }
+            break;
+        }
+
+        Ok(match best {
+            Some(step) => Alignment::At(step as u128),
+            None => Alignment::Never,
+        })
+    }
+
+    fn reachable_from(&self, start: usize) -> HashSet<usize> {
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for (_, &next) in &self.graph[node].destinations {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    // Graphviz, for looking at the network with `dot -Tsvg`. Edges a ghost keeps taking once it's
+    // looping get that ghost's colour, several colours when ghosts share an edge.
+    fn write_dot(
+        &self,
+        out: &mut impl Write,
+        from: Option<usize>,
+        ghosts: &[GhostCycle],
+    ) -> io::Result<()> {
+        const COLORS: [&str; 8] = [
+            "red",
+            "blue",
+            "darkgreen",
+            "orange",
+            "purple",
+            "brown",
+            "deeppink",
+            "cyan4",
+        ];
+
+        let shown = match from {
+            Some(start) => self.reachable_from(start),
+            None => (0..self.graph.len()).collect(),
+        };
+
+        let mut loop_edges: HashMap<(usize, Turn), Vec<&str>> = HashMap::new();
+        for (i, ghost) in ghosts.iter().enumerate() {
+            if !shown.contains(&ghost.start) {
+                continue;
+            }
+            let mut state = (ghost.start, 0);
+            for _ in 0..ghost.tail {
+                state = self.step(&state);
+            }
+            let mut edges = HashSet::new();
+            for _ in 0..ghost.period {
+                edges.insert((state.0, self.turns[state.1]));
+                state = self.step(&state);
+            }
+            for edge in edges {
+                loop_edges
+                    .entry(edge)
+                    .or_default()
+                    .push(COLORS[i % COLORS.len()]);
+            }
+        }
+
+        writeln!(out, "digraph network {{")?;
+        for (i, node) in self.graph.iter().enumerate() {
+            if !shown.contains(&i) {
+                continue;
+            }
+            let style = match node.node_type {
+                NodeType::Starting => " [shape=box, style=filled, fillcolor=palegreen]",
+                NodeType::Ending => " [shape=doublecircle, style=filled, fillcolor=lightpink]",
+                NodeType::None => "",
+            };
+            writeln!(out, "  \"{}\"{};", self.names[i], style)?;
+        }
+        for (i, node) in self.graph.iter().enumerate() {
+            if !shown.contains(&i) {
+                continue;
+            }
+            for (turn, &next) in &node.destinations {
+                let color = match loop_edges.get(&(i, turn)) {
+                    Some(colors) => format!(", color=\"{}\", penwidth=2", colors.join(":")),
+                    None => String::new(),
+                };
+                writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\" [label={}{}];",
+                    self.names[i],
+                    self.names[next],
+                    turn.to_char(),
+                    color
+                )?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    // Only right if every ghost loops back to its first ending exactly as often as it took to get there.
+    // None when some ghost never gets to an ending node at all.
+    fn lcm_shortcut(ghosts: &[GhostCycle]) -> Option<u128> {
+        ghosts
+            .iter()
+            .map(|x| x.hits.first().map(|x| *x as u128))
+            .try_fold(1, |a, b| math::lcm(a, b?))
     }
 }
 
 fn main() {
     let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
-    dbg!(&input);
-    dbg!(input.solve());
+
+    // `--dot` prints the network as Graphviz instead, `--dot NAME` only what can be reached from NAME.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(position) = args.iter().position(|x| x == "--dot") {
+        let from = args.get(position + 1).map(|name| {
+            input
+                .names
+                .iter()
+                .position(|x| x == name)
+                .unwrap_or_else(|| {
+                    eprintln!("there's no node called {:?}", name);
+                    process::exit(1);
+                })
+        });
+        input
+            .write_dot(&mut io::stdout().lock(), from, &input.ghost_cycles())
+            .unwrap();
+        return;
+    }
+
+    let names = |x: &[usize]| {
+        x.iter()
+            .map(|x| input.names[*x].as_str())
+            .collect::<Vec<_>>()
+    };
+    dbg!(
+        input.graph.len(),
+        names(&input.starting),
+        names(&input.ending)
+    );
+
+    let ghosts = input.ghost_cycles();
+    for ghost in &ghosts {
+        println!(
+            "{}: tail {}, period {}, ending at {:?}",
+            input.names[ghost.start], ghost.tail, ghost.period, ghost.hits
+        );
+    }
+
+    let alignment = Input::earliest_alignment(&ghosts).unwrap();
+    match alignment {
+        Alignment::At(step) => println!("everyone's on an ending node after {} steps", step),
+        Alignment::Never => println!("the ghosts never all line up"),
+    }
+
+    let shortcut = Input::lcm_shortcut(&ghosts);
+    if alignment != shortcut.map_or(Alignment::Never, Alignment::At) {
+        println!("warning: the LCM shortcut would have said {:?}", shortcut);
+    }
 }
@@ -0,0 +1,190 @@
+#![allow(dead_code)]
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// None when the result doesn't fit.
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn gcd_all<I: IntoIterator<Item = u128>>(iter: I) -> u128 {
+    iter.into_iter().fold(0, gcd)
+}
+
+pub fn lcm_all<I: IntoIterator<Item = u128>>(iter: I) -> Option<u128> {
+    iter.into_iter().try_fold(1, lcm)
+}
+
+// Returns (g, x, y) with a * x + b * y = g = gcd(a, b).
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+// x ≡ remainder (mod modulus)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Congruence {
+    pub remainder: i128,
+    pub modulus: i128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    NonPositiveModulus(Congruence),
+    // No number satisfies both at once.
+    Inconsistent(Congruence, Congruence),
+    Overflow,
+}
+
+impl Congruence {
+    fn normalized(self) -> Self {
+        Congruence {
+            remainder: self.remainder.rem_euclid(self.modulus),
+            modulus: self.modulus,
+        }
+    }
+
+    pub fn combine(self, other: Self) -> Result<Self, CrtError> {
+        for c in [self, other] {
+            if c.modulus <= 0 {
+                return Err(CrtError::NonPositiveModulus(c));
+            }
+        }
+        let (a, b) = (self.normalized(), other.normalized());
+
+        let (g, p, _) = extended_gcd(a.modulus, b.modulus);
+        let difference = b.remainder - a.remainder;
+        if difference % g != 0 {
+            return Err(CrtError::Inconsistent(self, other));
+        }
+
+        // a.remainder + a.modulus * k ≡ b.remainder (mod b.modulus), solve for k.
+        let reduced = b.modulus / g;
+        let k = ((difference / g) % reduced)
+            .checked_mul(p % reduced)
+            .ok_or(CrtError::Overflow)?
+            .rem_euclid(reduced);
+        let modulus = a.modulus.checked_mul(reduced).ok_or(CrtError::Overflow)?;
+        let remainder = a
+            .modulus
+            .checked_mul(k)
+            .and_then(|x| x.checked_add(a.remainder))
+            .ok_or(CrtError::Overflow)?;
+
+        Ok(Congruence {
+            remainder: remainder.rem_euclid(modulus),
+            modulus,
+        })
+    }
+}
+
+// The moduli don't need to be coprime.
+pub fn crt<I: IntoIterator<Item = Congruence>>(congruences: I) -> Result<Congruence, CrtError> {
+    congruences.into_iter().try_fold(
+        Congruence {
+            remainder: 0,
+            modulus: 1,
+        },
+        Congruence::combine,
+    )
+}
+
+// Floor of the square root. Newton's method starting above the root, never overflows.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let bits = 128 - n.leading_zeros();
+    let mut x: u128 = 1 << bits.div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// The full product, as (high, low) halves of a 256-bit number.
+pub fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let mask = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & mask);
+    let (b1, b0) = (b >> 64, b & mask);
+    let (middle, middle_carry) = (a1 * b0).overflowing_add(a0 * b1);
+    let (low, low_carry) = (a0 * b0).overflowing_add(middle << 64);
+    let high = a1 * b1 + (middle >> 64) + ((middle_carry as u128) << 64) + low_carry as u128;
+    (high, low)
+}
+
+// Floor of the square root of a (high, low) number. The root always fits in a u128.
+pub fn isqrt_wide(n: (u128, u128)) -> u128 {
+    let mut root: u128 = 0;
+    for bit in (0..128).rev() {
+        let candidate = root | 1 << bit;
+        // Tuples compare high half first, which is exactly the order of the numbers.
+        if mul_wide(candidate, candidate) <= n {
+            root = candidate;
+        }
+    }
+    root
+}
+
+fn mul_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % modulus;
+    }
+    // Too big for one multiplication, go through it by doubling instead.
+    let (mut a, mut b) = (a % modulus, b % modulus);
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, modulus);
+        }
+        a = add_mod(a, a, modulus);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    match a.checked_add(b) {
+        Some(sum) => sum % modulus,
+        None => a.wrapping_add(b).wrapping_sub(modulus) % modulus,
+    }
+}
+
+pub fn pow_mod(base: u128, mut exponent: u128, modulus: u128) -> u128 {
+    if modulus == 1 {
+        return 0;
+    }
+    let mut base = base % modulus;
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
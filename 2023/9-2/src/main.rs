@@ -1,13 +1,17 @@
 #![warn(missing_debug_implementations)]
 
-use std::{fs, str::FromStr};
+use rational::Rational;
+use std::{fmt, fs, str::FromStr};
+
+mod math;
+mod rational;
 
 #[derive(Debug)]
 struct ParseError {}
 
 #[derive(Debug)]
 struct Input {
-    histories: Vec<Vec<i32>>,
+    histories: Vec<Vec<i128>>,
 }
 
 impl FromStr for Input {
@@ -18,57 +22,241 @@ impl FromStr for Input {
             histories: s
                 .trim()
                 .split('\n')
-                .filter_map(|x| x.split(' ').map(|x| x.parse().ok()).rev().collect())
+                .filter_map(|x| x.split(' ').map(|x| x.parse().ok()).collect())
                 .collect(),
         })
     }
 }
 
-impl Input {
-    fn extrapolate(history: &Vec<i32>) -> Option<i32> {
-        if history.len() == 0 {
-            return None;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExtrapolationError {
+    Empty,
+    // Ran out of values before a row of differences came out all zero.
+    NeverZero,
+    Overflow,
+}
+
+#[derive(Debug)]
+struct Extrapolation {
+    // In the order they'd show up walking away from the history, so backwards ones go back in time.
+    values: Vec<i128>,
+    // How many rows of differences it took to get to all zeros.
+    depth: usize,
+}
+
+// Coefficients by power, `coefficients[i]` goes with x^i. The first value of the history is at x = 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Polynomial {
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    // Newton's forward differences: p(x) = sum over k of Δ^k y₀ · C(x, k), which then gets multiplied out.
+    fn fit(history: &[i128]) -> Result<Self, ExtrapolationError> {
+        let table = Input::difference_table(history)?;
+        let overflow = ExtrapolationError::Overflow;
+
+        let mut coefficients = vec![Rational::ZERO; table.len()];
+        // C(x, k) in powers of x, starting with C(x, 0) = 1.
+        let mut binomial = vec![Rational::ONE];
+        for (k, row) in table.iter().enumerate().take(table.len() - 1) {
+            let leading = Rational::from(row[0]);
+            for (i, b) in binomial.iter().enumerate() {
+                coefficients[i] = b
+                    .checked_mul(leading)
+                    .and_then(|x| x.checked_add(coefficients[i]))
+                    .ok_or(overflow)?;
+            }
+
+            // C(x, k + 1) = C(x, k) · (x - k) / (k + 1)
+            let k = k as i128;
+            let divisor = Rational::from(k + 1);
+            let mut next = vec![Rational::ZERO; binomial.len() + 1];
+            for (i, b) in binomial.iter().enumerate() {
+                let b = b.checked_div(divisor).ok_or(overflow)?;
+                next[i + 1] = next[i + 1].checked_add(b).ok_or(overflow)?;
+                next[i] = b
+                    .checked_mul(Rational::from(-k))
+                    .and_then(|x| x.checked_add(next[i]))
+                    .ok_or(overflow)?;
+            }
+            binomial = next;
+        }
+
+        while coefficients.last().is_some_and(|x| x.is_zero()) {
+            coefficients.pop();
         }
-        let mut relativity_table = vec![history.clone()];
-        let mut index = 0;
-
-        loop {
-            let mut relativity = vec![];
-            for i in 0..(relativity_table[index].len() - 1) {
-                let difference = relativity_table[index][i + 1] - relativity_table[index][i];
-                relativity.push(difference);
+        let polynomial = Polynomial { coefficients };
+
+        // The table hit zeros before running out of values, so this has to hold. Still cheap to check.
+        for (x, y) in history.iter().enumerate() {
+            if polynomial.evaluate(x as i128) != Some(Rational::from(*y)) {
+                return Err(ExtrapolationError::NeverZero);
             }
-            relativity_table.push(relativity);
-            index += 1;
+        }
+        Ok(polynomial)
+    }
+
+    // None for the zero polynomial.
+    fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    fn evaluate(&self, x: i128) -> Option<Rational> {
+        let x = Rational::from(x);
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(Rational::ZERO, |sum, c| sum.checked_mul(x)?.checked_add(*c))
+    }
+}
 
-            if relativity_table[index].iter().all(|x| *x == 0) {
-                relativity_table[index].push(0);
-                break;
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (power, c) in self.coefficients.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
             }
+            let negative = c.numerator() < 0;
+            match (first, negative) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => (),
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            first = false;
+
+            let magnitude = Rational::new(c.numerator().abs(), c.denominator()).unwrap();
+            if magnitude != Rational::ONE || power == 0 {
+                write!(f, "{}", magnitude)?;
+                if power > 0 {
+                    write!(f, " ")?;
+                }
+            }
+            match power {
+                0 => (),
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+        if first {
+            write!(f, "0")?;
         }
+        Ok(())
+    }
+}
 
-        for i in (0..index).rev() {
-            let difference = *(relativity_table[i + 1].last().unwrap());
-            let before = *relativity_table[i].last().unwrap();
-            relativity_table[i].push(before + difference);
+impl Input {
+    // The history itself, then its differences, down to the first row that's all zeros.
+    fn difference_table(history: &[i128]) -> Result<Vec<Vec<i128>>, ExtrapolationError> {
+        if history.is_empty() {
+            return Err(ExtrapolationError::Empty);
         }
-        dbg!(&relativity_table);
+        let mut table = vec![history.to_vec()];
 
-        relativity_table[0].last().cloned()
+        while !table.last().unwrap().iter().all(|x| *x == 0) {
+            let row = table.last().unwrap();
+            if row.len() == 1 {
+                return Err(ExtrapolationError::NeverZero);
+            }
+            let differences = row
+                .windows(2)
+                .map(|x| x[1].checked_sub(x[0]).ok_or(ExtrapolationError::Overflow))
+                .collect::<Result<_, _>>()?;
+            table.push(differences);
+        }
+        Ok(table)
     }
 
-    fn solve(&self) -> Option<i32> {
-        let mut sum = 0;
+    fn extrapolate(
+        history: &[i128],
+        direction: Direction,
+        steps: usize,
+    ) -> Result<Extrapolation, ExtrapolationError> {
+        let table = Self::difference_table(history)?;
+        let depth = table.len() - 1;
+
+        // Only the edge we're growing matters, the bottom row stays zero.
+        let mut edge: Vec<i128> = table
+            .iter()
+            .map(|x| match direction {
+                Direction::Forward => *x.last().unwrap(),
+                Direction::Backward => x[0],
+            })
+            .collect();
 
-        for history in &self.histories {
-            sum += Self::extrapolate(history)?;
+        let mut values = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            for i in (0..depth).rev() {
+                edge[i] = match direction {
+                    Direction::Forward => edge[i].checked_add(edge[i + 1]),
+                    Direction::Backward => edge[i].checked_sub(edge[i + 1]),
+                }
+                .ok_or(ExtrapolationError::Overflow)?;
+            }
+            values.push(edge[0]);
         }
-        Some(sum)
+
+        Ok(Extrapolation { values, depth })
+    }
+
+    fn solve(&self, direction: Direction) -> Result<i128, ExtrapolationError> {
+        self.histories.iter().try_fold(0i128, |sum, history| {
+            let next = Self::extrapolate(history, direction, 1)?.values[0];
+            sum.checked_add(next).ok_or(ExtrapolationError::Overflow)
+        })
     }
 }
 
 fn main() {
     let input: Input = fs::read_to_string("input").unwrap().parse().unwrap();
 
-    dbg!(&input.solve());
+    // Histories that don't work out get reported here and left out of everything after.
+    let mut deepest = None;
+    let mut polynomials = vec![];
+    for (i, history) in input.histories.iter().enumerate() {
+        match Input::extrapolate(history, Direction::Forward, 0)
+            .and_then(|x| Ok((x.depth, Polynomial::fit(history)?)))
+        {
+            Ok((depth, polynomial)) => {
+                deepest = deepest.max(Some(depth));
+                polynomials.push((history, polynomial));
+            }
+            Err(e) => println!("history {}: {:?}", i + 1, e),
+        }
+    }
+    dbg!(deepest);
+
+    // `--polynomials` shows the formula behind every history.
+    if std::env::args().any(|x| x == "--polynomials") {
+        for (history, polynomial) in &polynomials {
+            println!(
+                "degree {:>2} of at most {:>2}: {}",
+                polynomial
+                    .degree()
+                    .map_or("-".to_string(), |x| x.to_string()),
+                history.len() - 1,
+                polynomial
+            );
+        }
+    }
+
+    // Going straight to the index has to agree with walking the table.
+    let at = |index: fn(usize) -> i128| -> Option<i128> {
+        polynomials
+            .iter()
+            .map(|(h, p)| p.evaluate(index(h.len()))?.to_integer())
+            .sum()
+    };
+    dbg!(at(|n| n as i128), at(|_| -1));
+
+    dbg!(&input.solve(Direction::Forward));
+    dbg!(&input.solve(Direction::Backward));
 }
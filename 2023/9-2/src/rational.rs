@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+use crate::math::gcd;
+use std::fmt;
+
+// Always in lowest terms with a positive denominator, so the derived Eq is exact equality.
+// The checked operations return None when something doesn't fit in an i128.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Rational = Rational {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let g = gcd(numerator.unsigned_abs(), denominator.unsigned_abs()) as i128;
+        let (mut numerator, mut denominator) = (numerator / g, denominator / g);
+        if denominator < 0 {
+            numerator = numerator.checked_neg()?;
+            denominator = denominator.checked_neg()?;
+        }
+        Some(Rational {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Rational {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        // Going through the lcm keeps the intermediate numbers as small as they can be.
+        let g = gcd(
+            self.denominator.unsigned_abs(),
+            other.denominator.unsigned_abs(),
+        ) as i128;
+        let (a, b) = (self.denominator / g, other.denominator / g);
+        Rational::new(
+            self.numerator
+                .checked_mul(b)?
+                .checked_add(other.numerator.checked_mul(a)?)?,
+            self.denominator.checked_mul(b)?,
+        )
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        // Cancel crosswise first, then both products are already in lowest terms.
+        let g1 = gcd(
+            self.numerator.unsigned_abs(),
+            other.denominator.unsigned_abs(),
+        ) as i128;
+        let g2 = gcd(
+            other.numerator.unsigned_abs(),
+            self.denominator.unsigned_abs(),
+        ) as i128;
+        Rational::new(
+            (self.numerator / g1).checked_mul(other.numerator / g2)?,
+            (self.denominator / g2).checked_mul(other.denominator / g1)?,
+        )
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        self.checked_mul(Rational::new(other.denominator, other.numerator)?)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            _ => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}